@@ -1,5 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use huon::{DecoderOptions, de::from_str, test_list_model::CodeInfo, test_model::Person};
+use huon::{
+    DecoderOptions, de::from_str, parser::parse, test_list_model::CodeInfo, test_model::Person,
+};
 use std::{fs, hint::black_box, time::Duration};
 
 fn parsing_benchmark(c: &mut Criterion) {
@@ -20,6 +22,12 @@ fn parsing_benchmark(c: &mut Criterion) {
         });
     });
 
+    // A single level of keys, which takes the parser's flat fast path
+    let input = fs::read_to_string("test_flat.huon").unwrap();
+    group.bench_function("test_flat.huon", |b| {
+        b.iter(|| parse(black_box(&input), DecoderOptions::default()).unwrap());
+    });
+
     group.finish();
 }
 
@@ -0,0 +1,34 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use huon::{EncoderOptions, ser::HuonSerializer, ser::to_string};
+use serde::Serialize;
+use std::{collections::BTreeMap, hint::black_box, time::Duration};
+
+fn serializing_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serializing");
+    group.measurement_time(Duration::from_secs(10));
+
+    let map: BTreeMap<String, i64> = (0..10_000).map(|i| (format!("key_{i}"), i)).collect();
+
+    // `to_string` reserves the output buffer up front based on the map's length
+    group.bench_function("large map (reserved)", |b| {
+        b.iter(|| to_string(black_box(&map), EncoderOptions::default()).unwrap());
+    });
+
+    // A plain writer grows the buffer as it goes
+    group.bench_function("large map (unreserved)", |b| {
+        b.iter_batched(
+            Vec::new,
+            |mut vec| {
+                let mut serializer = HuonSerializer::new(&mut vec, EncoderOptions::default());
+                black_box(&map).serialize(&mut serializer).unwrap();
+                vec
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, serializing_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+use std::{env, fs, process::ExitCode};
+
+use huon::{DecoderOptions, EncoderOptions, parser::value::HuonValue};
+
+const USAGE: &str = "Usage: huon <to-json|from-json> <file>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let [command, path] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let input = match fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Couldn't read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let output = match command.as_str() {
+        "to-json" => to_json(&input),
+        "from-json" => from_json(&input),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Ok(output) => {
+            println!("{}", output.trim_end());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn to_json(input: &str) -> Result<String, String> {
+    let value = HuonValue::parse(input, DecoderOptions::default())
+        .map_err(|err| err.with_context(input))?;
+
+    // going through `serde_json::Value` sorts the keys, so the output is stable
+    let json = serde_json::to_value(&value).map_err(|err| err.to_string())?;
+    serde_json::to_string_pretty(&json).map_err(|err| err.to_string())
+}
+
+fn from_json(input: &str) -> Result<String, String> {
+    let json: serde_json::Value = serde_json::from_str(input).map_err(|err| err.to_string())?;
+    huon::ser::to_string(&json, EncoderOptions::default()).map_err(|err| err.to_string())
+}
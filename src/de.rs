@@ -1,21 +1,26 @@
 use crate::{
-    parser::{value::HuonValue, Parser, ValueMap},
-    tokenizer::Tokenizer,
+    parser::{owned::HuonValueOwned, value::HuonValue, ValueMap},
     DecoderOptions,
 };
 use serde::{
     de::{self, Visitor},
-    forward_to_deserialize_any, Deserializer,
+    forward_to_deserialize_any, Deserialize, Deserializer,
 };
-use std::collections::{hash_map, VecDeque};
+use std::borrow::Cow;
+use std::collections::{hash_map, BTreeMap, HashMap, VecDeque};
+use std::fmt;
 
 pub struct HuonDeserializer<'de> {
     value: HuonValue<'de>,
 }
 
 impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
-    type Error = serde::de::value::Error;
+    type Error = DeserializeError;
 
+    /// `null` is reported through `visit_unit`, the same way other self-describing formats do it.
+    /// `Option`s never end up here since they go through `deserialize_option`.
+    /// Internally and adjacently tagged enums rely on this, serde buffers the whole value
+    /// through here before looking for the tag.
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -23,13 +28,14 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
         match self.value {
             HuonValue::Boolean(b) => visitor.visit_bool(b),
             HuonValue::Int(i) => visitor.visit_i64(i),
-            HuonValue::String(s) => visitor.visit_borrowed_str(s),
+            HuonValue::BigInt(i) => visitor.visit_i128(i),
+            HuonValue::String(s) => visit_cow_str(s, visitor),
             HuonValue::Float(f) => visitor.visit_f64(f),
-            HuonValue::Null => visitor.visit_none(),
+            HuonValue::Null => visitor.visit_unit(),
             HuonValue::Object(map) => visitor.visit_map(MapDeserializer::new(map)),
-            HuonValue::List(list) => visitor.visit_seq(SequenceDeserializer {
-                sequence: VecDeque::from(list),
-            }),
+            HuonValue::List(list) => {
+                visitor.visit_seq(SequenceDeserializer::new(VecDeque::from(list)))
+            }
         }
     }
 
@@ -39,7 +45,7 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
     {
         match self.value {
             HuonValue::Boolean(b) => visitor.visit_bool(b),
-            _ => Err(de::Error::custom("Expected bool")),
+            value => Err(invalid_type("bool", &value)),
         }
     }
 
@@ -49,7 +55,59 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
     {
         match self.value {
             HuonValue::Int(i) => visitor.visit_i64(i),
-            _ => Err(de::Error::custom("Expected i64")),
+            // lets the visitor report that it's out of range
+            HuonValue::BigInt(i) => visitor.visit_i128(i),
+            value => Err(invalid_type("i64", &value)),
+        }
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            HuonValue::Int(i) => visitor.visit_i128(i.into()),
+            HuonValue::BigInt(i) => visitor.visit_i128(i),
+            value => Err(invalid_type("i128", &value)),
+        }
+    }
+
+    /// Values past `i64::MAX` are read as [`HuonValue::BigInt`], which fits them too.
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let int = into_i128(self.value, "u64")?;
+        let u = u64::try_from(int).map_err(|_| out_of_range(int, "u64"))?;
+        visitor.visit_u64(u)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let int = into_i128(self.value, "u128")?;
+        let u = u128::try_from(int).map_err(|_| out_of_range(int, "u128"))?;
+        visitor.visit_u128(u)
+    }
+
+    /// A `char` is written as a string holding exactly one character.
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            HuonValue::String(s) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => Err(de::Error::invalid_value(
+                        de::Unexpected::Str(&s),
+                        &"a single character",
+                    )),
+                }
+            }
+            value => Err(invalid_type("char", &value)),
         }
     }
 
@@ -58,18 +116,19 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
         V: Visitor<'de>,
     {
         match self.value {
-            HuonValue::String(s) => visitor.visit_string(s.to_string()),
-            _ => Err(de::Error::custom("Expected string")),
+            HuonValue::String(s) => visitor.visit_string(s.into_owned()),
+            value => Err(invalid_type("string", &value)),
         }
     }
 
+    /// Borrows from the input, so a `&'de str` doesn't allocate.
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
         match self.value {
-            HuonValue::String(s) => visitor.visit_borrowed_str(s),
-            _ => Err(de::Error::custom("Expected str")),
+            HuonValue::String(s) => visit_cow_str(s, visitor),
+            value => Err(invalid_type("str", &value)),
         }
     }
 
@@ -80,10 +139,45 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
         match self.value {
             // `map` is `&'de ValueMap<'de>`, so `MapDeserializer::new(map)` is correct.
             HuonValue::Object(map) => visitor.visit_map(MapDeserializer::new(map)),
-            _ => Err(de::Error::custom("Expected map")),
+            value => Err(invalid_type("map", &value)),
         }
     }
 
+    /// Objects are read as a sequence of `(key, value)` pairs, e.g. into a `Vec<(String, T)>`.
+    /// Parsed objects don't remember the order of their keys, so neither do the pairs. They come
+    /// in no particular order, which may differ from the document's and from run to run.
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let sequence = match self.value {
+            HuonValue::List(list) => VecDeque::from(list),
+            HuonValue::Object(map) => map
+                .into_iter()
+                .map(|(key, value)| HuonValue::List(vec![HuonValue::from(key), value]))
+                .collect(),
+            value => return Err(invalid_type("list", &value)),
+        };
+
+        visitor.visit_seq(SequenceDeserializer::new(sequence))
+    }
+
+    /// Bytes are collected from a list of ints into a new buffer, so there's nothing in the input
+    /// a `&'de [u8]` could borrow and it's rejected. Byte fields have to own their bytes.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(&into_bytes(self.value)?)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_byte_buf(into_bytes(self.value)?)
+    }
+
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
@@ -107,6 +201,39 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
         visitor.visit_newtype_struct(self)
     }
 
+    /// Unit variants are written as their name, e.g. `kind: "Circle"`.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            HuonValue::String(s) => visitor.visit_enum(de::value::CowStrDeserializer::new(s)),
+            // unit variants are serialized as their index
+            HuonValue::Int(i) => {
+                let variant = usize::try_from(i)
+                    .ok()
+                    .and_then(|idx| variants.get(idx))
+                    .ok_or_else(|| {
+                        let expected = format!("a variant index in 0..{}", variants.len());
+                        de::Error::invalid_value(de::Unexpected::Signed(i), &expected.as_str())
+                    })?;
+
+                visitor.visit_enum(de::value::BorrowedStrDeserializer::new(variant))
+            }
+            // an externally tagged variant holding data, like `Ok: 5`
+            HuonValue::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            value => Err(invalid_type("variant", &value)),
+        }
+    }
+
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -117,15 +244,213 @@ impl<'de> Deserializer<'de> for HuonDeserializer<'de> {
         }
     }
 
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            // serde only notices lists that are too short, the rest would be dropped silently
+            HuonValue::List(list) if list.len() > len => {
+                Err(de::Error::invalid_length(list.len(), &visitor))
+            }
+            value => HuonDeserializer { value }.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
     forward_to_deserialize_any! {
-        i8 i16 i32 u8 u16 u32 u64 f32 f64 char bytes byte_buf unit unit_struct
-        seq tuple tuple_struct enum identifier ignored_any
+        i8 i16 i32 u8 u16 u32 f32 f64 unit unit_struct
+        identifier ignored_any
+    }
+}
+
+/// Hands `s` to `visitor` borrowed from the input if it is, and owned otherwise, like the
+/// text of a block scalar.
+fn visit_cow_str<'de, V>(s: Cow<'de, str>, visitor: V) -> Result<V::Value, DeserializeError>
+where
+    V: Visitor<'de>,
+{
+    match s {
+        Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+        Cow::Owned(s) => visitor.visit_string(s),
+    }
+}
+
+/// An enum variant written as an object with a single key, the variant's name.
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    value: HuonValue<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            HuonValue::Null => Ok(()),
+            value => Err(invalid_type("null", &value).in_key(self.variant)),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(HuonDeserializer { value: self.value })
+            .map_err(|e| e.in_key(self.variant))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        HuonDeserializer { value: self.value }
+            .deserialize_any(visitor)
+            .map_err(|e| e.in_key(self.variant))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        HuonDeserializer { value: self.value }
+            .deserialize_any(visitor)
+            .map_err(|e| e.in_key(self.variant))
+    }
+}
+
+/// Bytes are written as a list of ints, each in `0..=255`.
+fn into_bytes(value: HuonValue<'_>) -> Result<Vec<u8>, DeserializeError> {
+    let HuonValue::List(list) = value else {
+        return Err(invalid_type("list of bytes", &value));
+    };
+
+    list.into_iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            match value {
+                HuonValue::Int(i) => u8::try_from(i).map_err(|_| {
+                    de::Error::invalid_value(de::Unexpected::Signed(i), &"a byte in 0..=255")
+                }),
+                value => Err(invalid_type("byte", &value)),
+            }
+            .map_err(|e: DeserializeError| e.in_index(idx))
+        })
+        .collect()
+}
+
+/// The error for a value of the wrong type, e.g. `expected str, found int`.
+fn invalid_type(expected: &str, found: &HuonValue<'_>) -> DeserializeError {
+    de::Error::custom(format!("expected {expected}, found {}", found.type_name()))
+}
+
+fn out_of_range(int: i128, expected: &str) -> DeserializeError {
+    de::Error::custom(format!("{int} is out of range for {expected}"))
+}
+
+/// The value of an `Int` or a `BigInt`, for the integer types only the latter may fit.
+fn into_i128(value: HuonValue<'_>, expected: &str) -> Result<i128, DeserializeError> {
+    match value {
+        HuonValue::Int(i) => Ok(i.into()),
+        HuonValue::BigInt(i) => Ok(i),
+        value => Err(invalid_type(expected, &value)),
+    }
+}
+
+/// An error from deserializing a parsed value. It knows the path to the value that caused it,
+/// e.g. `job1.info.payrate.iteration: expected str, found int`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    path: String,
+    message: String,
+}
+
+impl DeserializeError {
+    /// The path to the failing value, like `job1.codes[1]`. Empty if it's the root.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The error without the path.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The error happened in the value of `key`. Paths are built from the inside out.
+    fn in_key(mut self, key: &str) -> Self {
+        self.path = match self.path.as_str() {
+            "" => key.to_string(),
+            path if path.starts_with('[') => format!("{key}{path}"),
+            path => format!("{key}.{path}"),
+        };
+        self
+    }
+
+    /// The error happened in the list entry at `idx`.
+    fn in_index(mut self, idx: usize) -> Self {
+        self.path = match self.path.as_str() {
+            "" => format!("[{idx}]"),
+            path if path.starts_with('[') => format!("[{idx}]{path}"),
+            path => format!("[{idx}].{path}"),
+        };
+        self
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeserializeError {
+            path: String::new(),
+            message: msg.to_string(),
+        }
     }
 }
 
 struct MapDeserializer<'de> {
     iter: hash_map::IntoIter<&'de str, HuonValue<'de>>,
-    next_value: Option<HuonValue<'de>>,
+    next_value: Option<(&'de str, HuonValue<'de>)>,
 }
 
 impl<'de> MapDeserializer<'de> {
@@ -139,7 +464,7 @@ impl<'de> MapDeserializer<'de> {
 
 // The MapAccess impl is for 'de
 impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
-    type Error = de::value::Error;
+    type Error = DeserializeError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
@@ -147,9 +472,8 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
     {
         match self.iter.next() {
             Some((key, value)) => {
-                self.next_value = Some(value);
-                let key_deserializer = de::IntoDeserializer::into_deserializer(key);
-                seed.deserialize(key_deserializer).map(Some)
+                self.next_value = Some((key, value));
+                seed.deserialize(KeyDeserializer(key)).map(Some)
             }
             None => Ok(None),
         }
@@ -160,9 +484,10 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
         V: de::DeserializeSeed<'de>,
     {
         match self.next_value.take() {
-            Some(value) => {
+            Some((key, value)) => {
                 let value_deserializer = HuonDeserializer { value };
                 seed.deserialize(value_deserializer)
+                    .map_err(|e| e.in_key(key))
             }
             None => Err(de::Error::custom(
                 "Called next_value_seed before next_key_seed",
@@ -171,34 +496,241 @@ impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
     }
 }
 
+/// A map key. Keys are always strings, but integer ones like `` `1` `` are parsed back for
+/// maps keyed by integers.
+struct KeyDeserializer<'de>(&'de str);
+
+macro_rules! deserialize_int_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0.parse() {
+                    Ok(int) => visitor.$visit(int),
+                    // let the visitor report it as the wrong type
+                    Err(_) => visitor.visit_borrowed_str(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::value::BorrowedStrDeserializer::new(self.0).deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_int_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf option unit unit_struct newtype_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
 struct SequenceDeserializer<'de> {
     sequence: VecDeque<HuonValue<'de>>,
+    idx: usize,
+}
+
+impl<'de> SequenceDeserializer<'de> {
+    fn new(sequence: VecDeque<HuonValue<'de>>) -> Self {
+        Self { sequence, idx: 0 }
+    }
 }
 
 impl<'de> de::SeqAccess<'de> for SequenceDeserializer<'de> {
-    type Error = de::value::Error;
+    type Error = DeserializeError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
         T: de::DeserializeSeed<'de>,
     {
+        let idx = self.idx;
+        self.idx += 1;
+
         self.sequence
             .pop_front()
             .map(|val| {
                 let value_deserializer = HuonDeserializer { value: val };
                 seed.deserialize(value_deserializer)
+                    .map_err(|e| e.in_index(idx))
             })
             .transpose()
     }
 }
 
-#[derive(Debug)]
+impl<'de: 'a, 'a> Deserialize<'de> for HuonValue<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(HuonValueVisitor)
+    }
+}
+
+struct HuonValueVisitor;
+
+impl<'de> Visitor<'de> for HuonValueVisitor {
+    type Value = HuonValue<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a huon value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(HuonValue::Boolean(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(HuonValue::Int(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(i64::try_from(v).map_or(HuonValue::BigInt(v.into()), HuonValue::Int))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(i64::try_from(v).map_or(HuonValue::BigInt(v), HuonValue::Int))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        i128::try_from(v)
+            .map(|v| i64::try_from(v).map_or(HuonValue::BigInt(v), HuonValue::Int))
+            .map_err(|_| {
+                de::Error::invalid_value(de::Unexpected::Other("u128 above i128::MAX"), &self)
+            })
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(HuonValue::Float(v))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(HuonValue::from(v))
+    }
+
+    /// Text that doesn't borrow from the input, like a block scalar, is kept as an owned string.
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(HuonValue::String(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(HuonValue::String(Cow::Owned(v)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(HuonValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(HuonValue::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HuonValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            list.push(value);
+        }
+        Ok(HuonValue::List(list))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut object = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(HuonValue::Object(object))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
 pub enum HuonDeserializeError<'de> {
-    SerdeError(serde::de::value::Error),
+    #[error(transparent)]
+    SerdeError(DeserializeError),
+    #[error(transparent)]
     ParserError(crate::parser::ParserError<'de>),
+    #[error(transparent)]
     TokenizerError(crate::tokenizer::TokenizerError),
 }
 
+impl HuonDeserializeError<'_> {
+    /// Detaches the error from the input by stringifying the parts that borrow from it.
+    #[must_use]
+    pub fn into_owned(self) -> HuonDeserializeErrorOwned {
+        match self {
+            HuonDeserializeError::SerdeError(e) => HuonDeserializeErrorOwned::SerdeError(e),
+            HuonDeserializeError::ParserError(e) => {
+                HuonDeserializeErrorOwned::ParserError(e.to_string())
+            }
+            HuonDeserializeError::TokenizerError(e) => HuonDeserializeErrorOwned::TokenizerError(e),
+        }
+    }
+}
+
+/// A [`HuonDeserializeError`] that doesn't borrow from the input.
+#[derive(Debug, thiserror::Error)]
+pub enum HuonDeserializeErrorOwned {
+    #[error(transparent)]
+    SerdeError(DeserializeError),
+    #[error("{_0}")]
+    ParserError(String),
+    #[error(transparent)]
+    TokenizerError(crate::tokenizer::TokenizerError),
+}
+
+impl From<HuonDeserializeError<'_>> for HuonDeserializeErrorOwned {
+    fn from(e: HuonDeserializeError<'_>) -> Self {
+        e.into_owned()
+    }
+}
+
 pub fn from_str<'de, T>(
     s: &'de str,
     options: DecoderOptions,
@@ -206,16 +738,41 @@ pub fn from_str<'de, T>(
 where
     T: serde::Deserialize<'de>,
 {
-    let tokenizer = Tokenizer::new(s);
+    let value_tree = HuonValue::parse(s, options).map_err(HuonDeserializeError::ParserError)?;
+
+    from_value(value_tree).map_err(HuonDeserializeError::SerdeError)
+}
+
+/// Parses every layer and deep merges them from left to right, so later layers override
+/// earlier ones, then deserializes `T` from the result. See [`HuonValueOwned::merge`].
+pub fn from_layers<'a, T>(
+    layers: &[&'a str],
+    options: DecoderOptions,
+) -> Result<T, HuonDeserializeError<'a>>
+where
+    T: de::DeserializeOwned,
+{
+    let mut merged = HuonValueOwned::Object(BTreeMap::new());
 
-    let parsed =
-        Parser::parse(tokenizer, options).map_err(HuonDeserializeError::ParserError)?;
+    for layer in layers {
+        let value = HuonValue::parse(layer, options).map_err(HuonDeserializeError::ParserError)?;
+        merged.merge(value.into());
+    }
 
-    let value_tree = HuonValue::Object(parsed);
+    from_value(merged.as_value()).map_err(HuonDeserializeError::SerdeError)
+}
 
-    let deserializer = HuonDeserializer { value: value_tree };
+/// Deserializes `T` from an already parsed value.
+///
+/// To read one document into several types, parse it once with [`HuonValue::parse`] and pass
+/// each call a clone of the value, which is cheap since strings and keys borrow from the input.
+pub fn from_value<'de, T>(value: HuonValue<'de>) -> Result<T, DeserializeError>
+where
+    T: serde::Deserialize<'de>,
+{
+    let deserializer = HuonDeserializer { value };
 
-    T::deserialize(deserializer).map_err(HuonDeserializeError::SerdeError)
+    T::deserialize(deserializer)
 }
 
 #[cfg(test)]
@@ -225,6 +782,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use crate::{
+        parser::ParserError,
         test_list_model::{CodeInfo, TestCodes},
         test_model::{Job, JobCategory, JobInfo, NewType, PayRate, Person},
     };
@@ -302,8 +860,12 @@ mod tests {
             name: "General Access""#}
         .to_owned();
 
-        let code_info: CodeInfo =
-            from_str(&input, DecoderOptions { indent: 2 }).expect("Deserialization failed");
+        let options = DecoderOptions {
+            indent: 2,
+            ..Default::default()
+        };
+
+        let code_info: CodeInfo = from_str(&input, options).expect("Deserialization failed");
 
         let expected_code_info = CodeInfo {
             test_codes: TestCodes {
@@ -337,4 +899,702 @@ use serde::Deserialize;
 
         assert_eq!(bla, expected);
     }
+
+    #[test]
+    fn test_deserialize_mixed_list_into_json_values() {
+        #[derive(Debug, Deserialize)]
+        struct Mixed {
+            values: Vec<serde_json::Value>,
+        }
+
+        let input = r#"values: [1 "a" true null -2.5]"#;
+
+        let mixed: Mixed =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(
+            mixed.values,
+            vec![
+                serde_json::json!(1),
+                serde_json::json!("a"),
+                serde_json::json!(true),
+                serde_json::Value::Null,
+                serde_json::json!(-2.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_mixed_list_into_huon_values() {
+        #[derive(Debug, Deserialize)]
+        struct Mixed<'a> {
+            #[serde(borrow)]
+            values: Vec<HuonValue<'a>>,
+        }
+
+        let input = r#"values: [1 "a" true null]"#;
+
+        let mixed: Mixed =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(
+            mixed.values,
+            vec![
+                HuonValue::Int(1),
+                HuonValue::String("a".into()),
+                HuonValue::Boolean(true),
+                HuonValue::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_into_owned() {
+        // The input is dropped at the end of `load`, the error has to outlive it.
+        fn load() -> Result<CodeInfo, HuonDeserializeErrorOwned> {
+            let input = "name: \"John\"\n1job1: \"swe\"".to_owned();
+            Ok(from_str(&input, DecoderOptions::default())?)
+        }
+
+        let err = load().unwrap_err();
+
+        assert!(matches!(err, HuonDeserializeErrorOwned::ParserError(_)));
+        assert_eq!(err.to_string(), "The identifier '1job1' is not valid");
+    }
+
+    #[test]
+    fn test_deserialize_object_into_pairs() {
+        #[derive(Debug, Deserialize)]
+        struct Scores {
+            scores: Vec<(String, i64)>,
+        }
+
+        let input = indoc! {"
+            scores:
+                alice: 3
+                bob: 5"};
+
+        let mut scores: Scores =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        // the pairs aren't in document order, objects don't keep one
+        scores.scores.sort();
+
+        assert_eq!(
+            scores.scores,
+            vec![("alice".to_string(), 3), ("bob".to_string(), 5)]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bytes() {
+        struct Bytes(Vec<u8>);
+
+        impl<'de> Deserialize<'de> for Bytes {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct BytesVisitor;
+
+                impl Visitor<'_> for BytesVisitor {
+                    type Value = Bytes;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str("bytes")
+                    }
+
+                    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                        Ok(Bytes(v))
+                    }
+                }
+
+                deserializer.deserialize_byte_buf(BytesVisitor)
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct Data {
+            list: Vec<u8>,
+            buf: Bytes,
+        }
+
+        let data: Data = from_str("list: [1 2 255]\nbuf: [1 2 255]", DecoderOptions::default())
+            .expect("failed to deserialize");
+
+        assert_eq!(data.list, [1, 2, 255]);
+        assert_eq!(data.buf.0, [1, 2, 255]);
+
+        let err = from_str::<Data>("list: [1]\nbuf: [1 256]", DecoderOptions::default())
+            .map(|_| ())
+            .unwrap_err();
+        assert!(err.to_string().contains("256"), "{err}");
+
+        let err = from_str::<Data>("list: [1 256]\nbuf: [1]", DecoderOptions::default())
+            .map(|_| ())
+            .unwrap_err();
+        assert!(err.to_string().contains("256"), "{err}");
+    }
+
+    #[test]
+    fn test_deserialize_skipped_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            #[serde(skip_deserializing)]
+            cache: Vec<i64>,
+            #[serde(skip_deserializing, default = "default_retries")]
+            retries: i64,
+        }
+
+        fn default_retries() -> i64 {
+            3
+        }
+
+        let expected = Config {
+            name: "huon".to_string(),
+            cache: Vec::new(),
+            retries: 3,
+        };
+
+        let inputs = [
+            "name: \"huon\"",
+            "name: \"huon\"\ncache: null\nretries: null",
+            "name: \"huon\"\ncache: [1 2]\nretries: 5",
+            "cache:\n    entries: [1 2]\nname: \"huon\"\nretries: \"many\"",
+        ];
+
+        for input in inputs {
+            let config: Config =
+                from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+            assert_eq!(config, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_error_path() {
+        let input = include_str!("../test.huon").replace("\"monthly\"", "5");
+
+        let err = from_str::<Person>(&input, DecoderOptions::default()).unwrap_err();
+        let HuonDeserializeError::SerdeError(err) = err else {
+            panic!("Expected a serde error, got {err}");
+        };
+
+        assert_eq!(err.path(), "job1.info.payrate.iteration");
+        assert_eq!(err.message(), "expected str, found int");
+        assert_eq!(
+            err.to_string(),
+            "job1.info.payrate.iteration: expected str, found int"
+        );
+
+        let input = indoc! {r#"
+            test_codes:
+                codes: [1.5 "2.5"]
+                info: "Passwords"
+            name: "General Access""#};
+
+        let err = from_str::<CodeInfo>(input, DecoderOptions::default()).unwrap_err();
+        assert!(
+            err.to_string().starts_with("test_codes.codes[1]: "),
+            "{err}"
+        );
+
+        let err = from_str::<Vec<(String, Vec<i64>)>>("a: [1 true]", DecoderOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().starts_with("[0][1][1]: "), "{err}");
+    }
+
+    #[test]
+    fn test_deserialize_top_level_values() {
+        let list: Vec<i64> = from_str("[1 2 3]", DecoderOptions::default()).unwrap();
+        assert_eq!(list, [1, 2, 3]);
+
+        let name: &str = from_str("\"John\"\n", DecoderOptions::default()).unwrap();
+        assert_eq!(name, "John");
+
+        let codes: Vec<Vec<i64>> =
+            from_str("[\n    [1 2]\n    [3]\n]", DecoderOptions::default()).unwrap();
+        assert_eq!(codes, [vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_deserialize_internally_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "kind")]
+        enum Shape {
+            Circle {
+                radius: f64,
+            },
+            Rect {
+                width: i64,
+                height: i64,
+                label: Option<String>,
+            },
+            Empty,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Drawing {
+            main: Shape,
+            extra: Vec<Shape>,
+        }
+
+        let input = indoc! {r#"
+            main:
+                kind: "Rect"
+                width: 3
+                height: 4
+                label: null
+            extra: [
+                { kind: "Circle", radius: 1.5 }
+                { kind: "Empty" }
+            ]"#};
+
+        let drawing: Drawing =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(
+            drawing,
+            Drawing {
+                main: Shape::Rect {
+                    width: 3,
+                    height: 4,
+                    label: None
+                },
+                extra: vec![Shape::Circle { radius: 1.5 }, Shape::Empty],
+            }
+        );
+
+        let err = from_str::<Drawing>(
+            "main:\n    kind: \"Triangle\"\nextra: []",
+            DecoderOptions::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("main.kind: unknown variant `Triangle`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_adjacently_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "t", content = "c")]
+        enum Message {
+            Ping,
+            Move { x: i64, y: i64 },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Messages {
+            first: Message,
+            second: Message,
+            rest: Vec<Message>,
+        }
+
+        let input = indoc! {r#"
+            first:
+                t: "Ping"
+            second:
+                t: "Move"
+                c:
+                    x: 1
+                    y: -2
+            rest: [{ t: "Ping" } { c: { x: 0, y: 5 }, t: "Move" }]"#};
+
+        let messages: Messages =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(
+            messages,
+            Messages {
+                first: Message::Ping,
+                second: Message::Move { x: 1, y: -2 },
+                rest: vec![Message::Ping, Message::Move { x: 0, y: 5 }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_chars() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Letters {
+            letters: Vec<char>,
+        }
+
+        let options = DecoderOptions {
+            quote_style: crate::QuoteStyle::Single,
+            ..Default::default()
+        };
+
+        let letters: Letters =
+            from_str("letters: ['a' 'b' 'c']", options).expect("failed to deserialize");
+        assert_eq!(letters.letters, ['a', 'b', 'c']);
+
+        let letters: Letters = from_str("letters: [\"ä\" \"😀\"]", DecoderOptions::default())
+            .expect("failed to deserialize");
+        assert_eq!(letters.letters, ['ä', '😀']);
+
+        let err = from_str::<Letters>("letters: ['a' 'bc']", options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "letters[1]: invalid value: string \"bc\", expected a single character"
+        );
+
+        let err = from_str::<Letters>("letters: ['a' 1]", options).unwrap_err();
+        assert_eq!(err.to_string(), "letters[1]: expected char, found int");
+    }
+
+    #[test]
+    fn test_from_layers() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server {
+            host: String,
+            port: i64,
+            tags: Vec<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            debug: bool,
+            server: Server,
+        }
+
+        let base = indoc! {r#"
+            name: "app"
+            debug: true
+            server:
+                host: "localhost"
+                port: 8080
+                tags: ["dev" "local"]"#};
+
+        let production = indoc! {r#"
+            debug: false
+            server:
+                host: "example.com"
+                tags: ["prod"]"#};
+
+        let config: Config = from_layers(&[base, production], DecoderOptions::default())
+            .expect("failed to deserialize");
+
+        assert_eq!(
+            config,
+            Config {
+                name: "app".to_string(),
+                debug: false,
+                server: Server {
+                    host: "example.com".to_string(),
+                    port: 8080,
+                    tags: vec!["prod".to_string()],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_optional_list_elements() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Readings {
+            values: Vec<Option<i64>>,
+        }
+
+        let readings: Readings = from_str("values: [1 null 3]", DecoderOptions::default())
+            .expect("failed to deserialize");
+
+        assert_eq!(
+            readings,
+            Readings {
+                values: vec![Some(1), None, Some(3)],
+            }
+        );
+
+        let values: Vec<Option<i64>> =
+            from_str("[null null]", DecoderOptions::default()).expect("failed to deserialize");
+        assert_eq!(values, vec![None, None]);
+    }
+
+    #[test]
+    fn test_deserialize_into_btree_map() {
+        let input = "zeta: 3\nalpha: 1\nmid: 2";
+
+        let map: BTreeMap<String, i64> =
+            from_str(input, DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("alpha".to_string(), 1),
+                ("mid".to_string(), 2),
+                ("zeta".to_string(), 3),
+            ]
+        );
+
+        let values: BTreeMap<String, HuonValue> =
+            from_str("b: [1]\na: true", DecoderOptions::default()).expect("failed to deserialize");
+
+        assert_eq!(values.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(values["a"], HuonValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_deserialize_fixed_size_array() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Point {
+            position: [f64; 3],
+        }
+
+        let point: Point = from_str("position: [1.0 2.0 3.0]", DecoderOptions::default()).unwrap();
+        assert_eq!(
+            point,
+            Point {
+                position: [1.0, 2.0, 3.0]
+            }
+        );
+
+        let err = from_str::<Point>("position: [1.0 2.0 3.0 4.0]", DecoderOptions::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "position: invalid length 4, expected an array of length 3"
+        );
+
+        assert!(from_str::<Point>("position: [1.0 2.0]", DecoderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_flattened_extras() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Person<'a> {
+            name: &'a str,
+            age: i64,
+            #[serde(flatten, borrow)]
+            extra: HashMap<String, HuonValue<'a>>,
+        }
+
+        let input = indoc! {r#"
+            name: "John"
+            nickname: "Johnny"
+            age: 32
+            tags: [1 2]
+            address:
+                city: "Berlin"
+        "#};
+
+        let person: Person = from_str(input, DecoderOptions::default()).unwrap();
+
+        assert_eq!(person.name, "John");
+        assert_eq!(person.age, 32);
+        assert_eq!(person.extra.len(), 3);
+        assert_eq!(person.extra["nickname"], HuonValue::String("Johnny".into()));
+        assert_eq!(
+            person.extra["tags"],
+            HuonValue::List(vec![HuonValue::Int(1), HuonValue::Int(2)])
+        );
+        assert_eq!(
+            person.extra["address"],
+            HuonValue::Object(HashMap::from([(
+                "city",
+                HuonValue::String("Berlin".into())
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unit_variant_from_index() {
+        #[derive(serde::Serialize, Deserialize, Debug, PartialEq)]
+        enum Color {
+            Red,
+            Green,
+            Blue,
+        }
+
+        #[derive(serde::Serialize, Deserialize, Debug, PartialEq)]
+        struct Theme {
+            color: Color,
+        }
+
+        let theme = Theme {
+            color: Color::Green,
+        };
+        let s = crate::ser::to_string(&theme, crate::EncoderOptions::default()).unwrap();
+        assert_eq!(s, "color: 1\n");
+
+        let parsed: Theme = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, theme);
+
+        let parsed: Theme = from_str("color: \"Blue\"", DecoderOptions::default()).unwrap();
+        assert_eq!(parsed.color, Color::Blue);
+
+        let err = from_str::<Theme>("color: 3", DecoderOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "color: invalid value: integer `3`, expected a variant index in 0..3"
+        );
+
+        assert!(from_str::<Theme>("color: -1", DecoderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_borrowed_str_owned_bytes() {
+        #[derive(Deserialize, Debug)]
+        struct Record<'a> {
+            name: &'a str,
+            data: Vec<u8>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Borrowed<'a> {
+            #[allow(dead_code)]
+            data: &'a [u8],
+        }
+
+        let input = "name: \"John\"\ndata: [1 2 255]";
+        let record: Record = from_str(input, DecoderOptions::default()).unwrap();
+
+        // `name` points into `input` instead of a copy of it
+        let range = input.as_bytes().as_ptr_range();
+        assert!(range.contains(&record.name.as_ptr()));
+        assert_eq!(record.name, "John");
+
+        assert!(!range.contains(&record.data.as_ptr()));
+        assert_eq!(record.data, vec![1, 2, 255]);
+
+        let err = from_str::<Borrowed>("data: [1 2]", DecoderOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "data: invalid type: byte array, expected a borrowed byte array"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_trailing_data() {
+        let err = from_str::<i64>("5 extra", DecoderOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            HuonDeserializeError::ParserError(ParserError::TrailingData { start: 2 })
+        ));
+
+        let options = DecoderOptions {
+            error_on_trailing_data: false,
+            ..Default::default()
+        };
+        assert_eq!(from_str::<i64>("5 extra", options).unwrap(), 5);
+        assert_eq!(
+            from_str::<Vec<i64>>("[1 2]\n[3]", options).unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_two_views_of_one_value() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server<'a> {
+            host: &'a str,
+            port: u16,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Logging {
+            level: String,
+        }
+
+        let input = indoc! {r#"
+            host: "localhost"
+            port: 8080
+            level: "debug"
+        "#};
+
+        let value = HuonValue::parse(input, DecoderOptions::default()).unwrap();
+
+        let server: Server = from_value(value.clone()).unwrap();
+        let logging: Logging = from_value(value).unwrap();
+
+        assert_eq!(
+            server,
+            Server {
+                host: "localhost",
+                port: 8080
+            }
+        );
+        assert_eq!(
+            logging,
+            Logging {
+                level: "debug".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_borrowed_str_list() {
+        let input = r#"["a" "b"]"#;
+        let list: Vec<&str> = from_str(input, DecoderOptions::default()).unwrap();
+
+        assert_eq!(list, vec!["a", "b"]);
+
+        let range = input.as_bytes().as_ptr_range();
+        assert!(list.iter().all(|s| range.contains(&s.as_ptr())));
+
+        let input = "tags: [\"x\" \"y\"]";
+        let map: HashMap<&str, Vec<&str>> = from_str(input, DecoderOptions::default()).unwrap();
+
+        let range = input.as_bytes().as_ptr_range();
+        assert_eq!(map["tags"], vec!["x", "y"]);
+        assert!(map["tags"].iter().all(|s| range.contains(&s.as_ptr())));
+    }
+
+    #[test]
+    fn test_deserialize_block_scalar() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Step {
+            run: String,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct BorrowedStep<'a> {
+            #[allow(dead_code)]
+            run: &'a str,
+        }
+
+        let input = "run: |\n    echo hi\n    echo bye\n";
+        let step: Step = from_str(input, DecoderOptions::default()).unwrap();
+        assert_eq!(
+            step,
+            Step {
+                run: "echo hi\necho bye\n".to_string()
+            }
+        );
+
+        // the text is put together, so there's nothing to borrow
+        let err = from_str::<BorrowedStep>(input, DecoderOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "run: invalid type: string \"echo hi\\necho bye\\n\", expected a borrowed string"
+        );
+
+        let steps: HashMap<&str, HuonValue> = from_str(input, DecoderOptions::default()).unwrap();
+        assert_eq!(steps["run"], HuonValue::from("echo hi\necho bye\n"));
+    }
+
+    #[test]
+    fn test_deserialize_big_ints_into_typed_fields() {
+        #[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
+        struct Ids {
+            small: u128,
+            big: u128,
+            list: Vec<u128>,
+        }
+
+        let ids = Ids {
+            small: 7,
+            big: i128::MAX as u128,
+            list: vec![u128::from(u64::MAX) + 1],
+        };
+
+        let s = crate::ser::to_string(&ids, crate::EncoderOptions::default()).unwrap();
+        let parsed: Ids = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, ids);
+
+        let err = from_str::<u128>("-1", DecoderOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "-1 is out of range for u128");
+    }
 }
@@ -1,7 +1,10 @@
 pub mod de;
 mod error;
 pub mod parser;
+pub mod scalar;
+pub mod schema;
 pub mod ser;
+pub mod serde_helpers;
 pub mod tokenizer;
 
 pub mod test_model;
@@ -10,6 +13,12 @@ pub mod test_list_model;
 
 pub use error::{Error, Result};
 
+use std::result::Result as StdResult;
+
+use de::{DeserializeError, HuonDeserializeError};
+use parser::{owned::HuonValueOwned, value::HuonValue};
+use ser::HuonSerializeError;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ListCommaStyle {
     /// No commas at all
@@ -22,10 +31,128 @@ pub enum ListCommaStyle {
     Trailing,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ListStyle {
+    /// All entries on one line: `[1 2 3]`
+    #[default]
+    Spaced,
+
+    /// Every entry on its own line, indented one level deeper than the list
+    Newline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum QuoteStyle {
+    /// `"string"`
+    #[default]
+    Double,
+
+    /// `'string'`
+    Single,
+}
+
+impl QuoteStyle {
+    #[must_use]
+    pub const fn char(self) -> char {
+        match self {
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+        }
+    }
+}
+
+/// How booleans are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum BoolStyle {
+    /// `true` and `false`
+    #[default]
+    TrueFalse,
+
+    /// `yes` and `no`, like some older config formats do
+    YesNo,
+}
+
+impl BoolStyle {
+    #[must_use]
+    pub const fn word(self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolStyle::TrueFalse, true) => "true",
+            (BoolStyle::TrueFalse, false) => "false",
+            (BoolStyle::YesNo, true) => "yes",
+            (BoolStyle::YesNo, false) => "no",
+        }
+    }
+}
+
+/// What the serializer indents lines with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum IndentChar {
+    /// [`EncoderOptions::indent`] spaces per level
+    #[default]
+    Space,
+
+    /// One tab per level, read them back with [`DecoderOptions::tab_width`] set to the indent
+    Tab,
+}
+
+/// What to do when an object has the same key more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DuplicateKeyMode {
+    /// The last value replaces the earlier ones
+    #[default]
+    LastWins,
+
+    /// Reject the document
+    Error,
+
+    /// Concatenate lists, so `items: [1 2]` and `items: [3]` read as `[1 2 3]`.
+    /// Any other value replaces the earlier one, like with `LastWins`.
+    AppendLists,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct EncoderOptions {
     pub indent: u8,
     pub list_comma_style: ListCommaStyle,
+    pub list_style: ListStyle,
+    pub quote_style: QuoteStyle,
+
+    /// Writes floats with exactly this many decimals, e.g. `3.14` for `3.14159` with `Some(2)`.
+    /// Anything past that is rounded away, so reading the output back gives the rounded value.
+    /// `None` writes the shortest representation that reads back as the exact same float.
+    pub float_precision: Option<usize>,
+
+    /// End the document with a line break, as POSIX text files do.
+    pub trailing_newline: bool,
+
+    /// Pad the values of sibling keys so they start in the same column,
+    /// see [`ser::to_string_pretty`]. Every map is buffered until its last key is known then,
+    /// without this the output is written straight to the writer as it's produced.
+    pub align_values: bool,
+
+    /// Spaced lists that would make their line longer than this are written in the
+    /// [`ListStyle::Newline`] style instead. Lists nested in lists only count their own indent.
+    pub max_line_width: Option<usize>,
+
+    /// Write nested objects holding just one scalar in braces on the line of their key,
+    /// like `category: { name: "IT" }`. The comments of keys in braces are left out.
+    pub inline_single_field: bool,
+
+    pub bool_style: BoolStyle,
+
+    /// Write chars without quotes, like `a` instead of `"a"`. That's how they used to be
+    /// written, but chars like `,` or `:` can't be read back then.
+    pub bare_chars: bool,
+
+    /// Write lists of strings, numbers or booleans sorted and without duplicates, like a set.
+    /// Chars and custom scalars count as strings and are sorted by their text. Other lists,
+    /// e.g. ones mixing kinds or holding objects, are written as they are.
+    pub dedup_lists: bool,
+
+    /// Indent with tabs instead of spaces. Line widths are still measured as if every
+    /// level was `indent` columns wide.
+    pub indent_char: IndentChar,
 }
 
 impl Default for EncoderOptions {
@@ -33,17 +160,268 @@ impl Default for EncoderOptions {
         Self {
             list_comma_style: ListCommaStyle::None,
             indent: 4,
+            list_style: ListStyle::Spaced,
+            quote_style: QuoteStyle::Double,
+            float_precision: None,
+            trailing_newline: true,
+            align_values: false,
+            max_line_width: None,
+            inline_single_field: false,
+            bool_style: BoolStyle::TrueFalse,
+            bare_chars: false,
+            dedup_lists: false,
+            indent_char: IndentChar::Space,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct DecoderOptions {
     pub indent: u8,
+
+    /// How many spaces a tab counts as. Tabs are rejected if this is `None`.
+    pub tab_width: Option<u8>,
+
+    /// The character strings are delimited with.
+    pub quote_style: QuoteStyle,
+
+    /// Read unquoted words that aren't keywords, like `red` in `color: red`, as strings
+    /// instead of rejecting them.
+    pub bareword_as_string: bool,
+
+    /// Round indentation that isn't a multiple of `indent` to the nearest level instead of
+    /// rejecting it, e.g. 3 spaces count as one level of 4. Each rounding is reported as a
+    /// [`ParseWarning`](parser::ParseWarning). Off by default, since a guess can be wrong.
+    pub lenient_indent: bool,
+
+    pub duplicate_keys: DuplicateKeyMode,
+
+    /// Accept numbers like `007`, which are read as `7`. When this is off they're rejected,
+    /// like JSON does, so a zero that matters doesn't get dropped silently.
+    pub allow_leading_zeros: bool,
+
+    /// How many columns every line is shifted to the right, for documents embedded in something
+    /// else that's indented. This much leading whitespace is ignored on each line, so the
+    /// document parses as if it started at column zero.
+    pub base_indent: usize,
+
+    /// With [`BoolStyle::YesNo`], `yes` and `no` are read as booleans too. `true` and `false`
+    /// always are.
+    pub bool_style: BoolStyle,
+
+    /// Read a word starting with this character as the text of a custom scalar, see
+    /// [`scalar::HuonScalar`]. The character has to be followed by a letter, digit or `_`,
+    /// so a `#` prefix still leaves `# comments` alone.
+    pub scalar_prefix: Option<char>,
+
+    /// Read numbers followed by `%`, like `5%`, as the fraction they stand for, `0.05`.
+    /// Deserialize them into a [`scalar::Percent`] to keep writing them that way.
+    pub percent_literals: bool,
+
+    /// Accept a nested object whose lines aren't indented deeper than its key, like
+    /// `person:` followed by `name: "John"` on the next line. The object then takes every line
+    /// at its own indentation that follows, up to the first one indented less.
+    pub allow_hanging_indent: bool,
+
+    /// Have the tokenizer emit `Token::RawInt` and `Token::RawFloat`, which keep the number as
+    /// it's written, like `1_000` or `0xFF`, next to its value. For tools that shouldn't
+    /// reformat numbers, parsing reads the same values either way.
+    pub keep_number_text: bool,
+
+    /// Error with `ParserError::TrailingData` if anything but whitespace and comments follows
+    /// a document that's a single list or scalar, like the `extra` in `5 extra`. When off,
+    /// parsing stops after the value and ignores the rest. Objects always span the whole input.
+    pub error_on_trailing_data: bool,
+
+    /// The character digits may be grouped with, like `1'000` with `'`. It has to sit in
+    /// between two digits. Characters that are part of a number or can follow one, like `,`,
+    /// a space or a letter, are rejected with
+    /// [`TokenizerError::InvalidDigitGroupSep`](tokenizer::TokenizerError::InvalidDigitGroupSep)
+    /// before anything is read. `None` allows no grouping at all.
+    pub digit_group_sep: Option<char>,
+}
+
+impl DecoderOptions {
+    /// Registers the scalar `T`, so its values are read without quotes.
+    #[must_use]
+    pub const fn with_scalar<T: scalar::HuonScalar>(mut self) -> Self {
+        self.scalar_prefix = Some(T::PREFIX);
+        self
+    }
+}
+
+/// Holds the options for both directions, so they only have to be configured once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Huon {
+    pub decoder: DecoderOptions,
+    pub encoder: EncoderOptions,
+}
+
+impl Huon {
+    /// See [`de::from_str`].
+    pub fn from_str<'de, T>(&self, s: &'de str) -> StdResult<T, HuonDeserializeError<'de>>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        de::from_str(s, self.decoder)
+    }
+
+    /// See [`ser::to_string`].
+    pub fn to_string<T>(&self, value: &T) -> StdResult<String, HuonSerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        ser::to_string(value, self.encoder)
+    }
+
+    /// See [`de::from_value`]. A value has already been parsed, so no options apply to it.
+    #[allow(clippy::unused_self)]
+    pub fn from_value<'de, T>(&self, value: HuonValue<'de>) -> StdResult<T, DeserializeError>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        de::from_value(value)
+    }
+
+    /// Serializes `value` into a [`HuonValueOwned`], the same way [`Huon::to_string`] writes it.
+    pub fn to_value<T>(&self, value: &T) -> StdResult<HuonValueOwned, HuonSerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let s = self.to_string(value)?;
+
+        HuonValue::parse(&s, self.read_back_options())
+            .map(HuonValueOwned::from)
+            .map_err(|e| HuonSerializeError::Custom(format!("Couldn't read the value back: {e}")))
+    }
+
+    /// The options that read back exactly what the encoder writes. Only the custom scalars and
+    /// percent literals are taken from the decoder, since the encoder writes those as they are.
+    fn read_back_options(&self) -> DecoderOptions {
+        let encoder = &self.encoder;
+
+        DecoderOptions {
+            indent: encoder.indent,
+            tab_width: (encoder.indent_char == IndentChar::Tab).then_some(encoder.indent),
+            quote_style: encoder.quote_style,
+            bareword_as_string: encoder.bare_chars,
+            bool_style: encoder.bool_style,
+            scalar_prefix: self.decoder.scalar_prefix,
+            percent_literals: self.decoder.percent_literals,
+            ..DecoderOptions::default()
+        }
+    }
 }
 
 impl Default for DecoderOptions {
     fn default() -> Self {
-        Self { indent: 4 }
+        Self {
+            indent: 4,
+            tab_width: None,
+            quote_style: QuoteStyle::Double,
+            bareword_as_string: false,
+            lenient_indent: false,
+            duplicate_keys: DuplicateKeyMode::LastWins,
+            allow_leading_zeros: true,
+            base_indent: 0,
+            bool_style: BoolStyle::TrueFalse,
+            scalar_prefix: None,
+            percent_literals: false,
+            allow_hanging_indent: false,
+            keep_number_text: false,
+            error_on_trailing_data: true,
+            digit_group_sep: Some('_'),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::test_list_model::{CodeInfo, TestCodes};
+
+    use super::*;
+
+    #[test]
+    fn test_facade_both_directions() {
+        let huon = Huon {
+            decoder: DecoderOptions {
+                indent: 2,
+                quote_style: QuoteStyle::Single,
+                ..Default::default()
+            },
+            encoder: EncoderOptions {
+                indent: 2,
+                list_comma_style: ListCommaStyle::Basic,
+                quote_style: QuoteStyle::Single,
+                ..Default::default()
+            },
+        };
+
+        let code_info = CodeInfo {
+            test_codes: TestCodes {
+                codes: vec![111.1, 333.3],
+                info: "Passwords".to_string(),
+            },
+            name: "General Access".to_string(),
+        };
+
+        let s = huon.to_string(&code_info).unwrap();
+        assert_eq!(
+            s,
+            "test_codes:\n  codes: [111.1, 333.3]\n  info: 'Passwords'\nname: 'General Access'\n"
+        );
+        assert_eq!(huon.from_str::<CodeInfo>(&s).unwrap(), code_info);
+
+        let value = huon.to_value(&code_info).unwrap();
+        let HuonValueOwned::Object(ref map) = value else {
+            panic!("Not an object");
+        };
+        assert_eq!(
+            map["name"],
+            HuonValueOwned::String("General Access".to_string())
+        );
+        assert_eq!(
+            huon.from_value::<CodeInfo>(value.as_value()).unwrap(),
+            code_info
+        );
+    }
+
+    #[test]
+    fn test_to_value_reads_back_any_encoding() {
+        let code_info = CodeInfo {
+            test_codes: TestCodes {
+                codes: vec![111.1, 333.3],
+                info: "Passwords".to_string(),
+            },
+            name: "General Access".to_string(),
+        };
+
+        let huon = Huon {
+            // nothing the encoder writes depends on these
+            decoder: DecoderOptions {
+                indent: 2,
+                base_indent: 8,
+                ..Default::default()
+            },
+            encoder: EncoderOptions {
+                indent: 3,
+                indent_char: IndentChar::Tab,
+                list_style: ListStyle::Newline,
+                quote_style: QuoteStyle::Single,
+                ..Default::default()
+            },
+        };
+
+        let value = huon.to_value(&code_info).unwrap();
+        assert_eq!(
+            huon.from_value::<CodeInfo>(value.as_value()).unwrap(),
+            code_info
+        );
+
+        let err = huon.to_value(&vec![f64::NAN]).unwrap_err();
+        assert!(err.to_string().contains("NaN"), "{err}");
     }
 }
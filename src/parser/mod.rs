@@ -1,12 +1,20 @@
 use {
     crate::{
+        scalar,
         tokenizer::{token::Token, Tokenizer, TokenizerError},
-        DecoderOptions,
+        DecoderOptions, DuplicateKeyMode,
     },
-    std::{cmp::Ordering, collections::HashMap, iter::Peekable},
-    value::HuonValue,
+    spanned::{Spanned, SpannedValue},
+    std::{
+        borrow::Cow,
+        cmp::Ordering,
+        collections::{hash_map::Entry, HashMap},
+    },
+    value::{HuonValue, PathSegment},
 };
 
+pub mod owned;
+pub mod spanned;
 pub mod value;
 
 type Result<'a, T> = std::result::Result<T, ParserError<'a>>;
@@ -16,31 +24,197 @@ pub enum ParserError<'a> {
     #[error("EOF")]
     Eof,
 
+    /// A token that can't be where it is, along with where that is.
     #[error("Invalid token: {_0:?}")]
-    InvalidToken(Token<'a>),
+    InvalidToken(Token<'a>, Span),
 
     #[error("Couldn't convert from: {_0:?}")]
     InvalidHuonValue(Token<'a>),
 
+    /// A line is indented deeper than it may be, both counts are in spaces.
+    #[error("Found {found} spaces of indentation, expected {expected}")]
+    UnexpectedIndent { found: usize, expected: usize },
+
+    /// An object has the same key twice, see [`DuplicateKeyMode::Error`].
+    #[error("Duplicate key: {_0}")]
+    DuplicateKey(&'a str),
+
+    /// A list without its closing `]`, `start` is the byte offset of the opening one.
+    #[error("The list starting at byte {start} is never closed")]
+    UnterminatedList { start: usize },
+
+    /// Something follows a document's single list or scalar, `start` is the byte offset it
+    /// starts at. See [`DecoderOptions::error_on_trailing_data`].
+    #[error("Unexpected data after the document at byte {start}")]
+    TrailingData { start: usize },
+
     #[error(transparent)]
     TokenizerError(#[from] TokenizerError),
 }
 
+impl ParserError<'_> {
+    /// Where in the input the error is, if that's known.
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParserError::InvalidToken(_, span) => Some(*span),
+            ParserError::UnterminatedList { start }
+            | ParserError::TrailingData { start }
+            | ParserError::TokenizerError(TokenizerError::UnterminatedString { start }) => {
+                Some(Span {
+                    start: *start,
+                    end: start + 1,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders the error along with the line of `source` it's in, pointing at the spot:
+    ///
+    /// ```text
+    /// Invalid token: Int(3)
+    ///  --> 2:8
+    ///   |
+    /// 2 | age: 3 3
+    ///   |        ^
+    /// ```
+    ///
+    /// `source` has to be the input that was parsed. Errors without a [`ParserError::span`]
+    /// are rendered as just the message.
+    #[must_use]
+    pub fn with_context(&self, source: &str) -> String {
+        let Some(span) = self.span() else {
+            return self.to_string();
+        };
+
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |idx| start + idx);
+
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        let line_number = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count();
+        let width = source[start..span.end.clamp(start, line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        format!(
+            "{self}\n{gutter}--> {line_number}:{}\n{gutter} |\n{line_number} | {line}\n{gutter} | {}{}",
+            column + 1,
+            " ".repeat(column),
+            "^".repeat(width),
+        )
+    }
+}
+
+#[cfg(feature = "codespan")]
+impl ParserError<'_> {
+    /// Converts the error into a `codespan-reporting` diagnostic for the file `file_id`,
+    /// labeling the spot from [`ParserError::span`] if it's known.
+    #[must_use]
+    pub fn to_diagnostic<FileId>(
+        &self,
+        file_id: FileId,
+    ) -> codespan_reporting::diagnostic::Diagnostic<FileId> {
+        use codespan_reporting::diagnostic::{Diagnostic, Label};
+
+        let diagnostic = Diagnostic::error().with_message(self.to_string());
+
+        match self.span() {
+            Some(span) => diagnostic.with_labels(vec![Label::primary(file_id, span)]),
+            None => diagnostic,
+        }
+    }
+}
+
+/// A range of bytes in the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl From<Span> for std::ops::Range<usize> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
 pub type ValueMap<'a> = HashMap<&'a str, HuonValue<'a>>;
 
+/// Numbers gathered while parsing a document, see [`parse_with_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// How many tokens were read, including whitespace and line breaks.
+    pub tokens: usize,
+    /// How many keys there are, in nested and braced objects as well.
+    pub keys: usize,
+    /// How many objects and lists the deepest value is in, counting the document itself.
+    /// So `a: 1` has a depth of 1, `a: [1]` one of 2.
+    pub max_depth: usize,
+    pub lines: usize,
+}
+
+/// A mistake the parser worked around instead of failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ParseWarning {
+    /// Indentation that isn't a multiple of the indent was rounded to the nearest level,
+    /// see [`DecoderOptions::lenient_indent`]. Both counts are in spaces.
+    #[error("Rounded {found} spaces of indentation to {rounded}")]
+    RoundedIndent { found: usize, rounded: usize },
+}
+
+/// The comments written above a key, by the path of keys leading to it.
+pub type Comments = HashMap<Vec<String>, Vec<String>>;
+
 pub struct Parser<'a> {
-    tokenizer: Peekable<Tokenizer<'a>>,
+    tokenizer: Tokenizer<'a>,
+    /// The next token, if it was looked at already.
+    peeked: Option<(Span, std::result::Result<Token<'a>, TokenizerError>)>,
+    /// Where the token that was looked at last is.
+    span: Span,
     collapse: usize,
     options: DecoderOptions,
+    /// The keys of the objects currently being parsed.
+    path: Vec<&'a str>,
+    /// Comments read since the last key, they belong to the next one.
+    pending_comments: Vec<&'a str>,
+    comments: Option<Comments>,
+    warnings: Vec<ParseWarning>,
+    stats: ParseStats,
+    /// How many objects and lists deep the parser currently is.
+    depth: usize,
+    /// Where each value is by its path, if spans are being kept.
+    spans: Option<HashMap<Vec<PathSegment<'a>>, Span>>,
+    /// The path of the value currently being parsed, including list indices unlike `path`.
+    value_path: Vec<PathSegment<'a>>,
+    /// Where the value that was parsed last ends.
+    value_end: usize,
 }
 
 impl<'a> Parser<'a> {
     #[must_use]
     pub fn new(tokenizer: Tokenizer<'a>, options: DecoderOptions) -> Self {
         Self {
-            tokenizer: tokenizer.peekable(),
+            tokenizer,
+            peeked: None,
+            span: Span::default(),
             collapse: 0,
             options,
+            path: Vec::new(),
+            pending_comments: Vec::new(),
+            comments: None,
+            warnings: Vec::new(),
+            stats: ParseStats::default(),
+            depth: 0,
+            spans: None,
+            value_path: Vec::new(),
+            value_end: 0,
         }
     }
 
@@ -49,17 +223,113 @@ impl<'a> Parser<'a> {
         parser.parse_object(0)
     }
 
-    /// A helper func to check if a token is whitespace with the expected indentation.
+    /// Like [`Parser::parse`], but also keeps the comments above every key.
+    pub fn parse_with_comments(
+        tokenizer: Tokenizer<'a>,
+        options: DecoderOptions,
+    ) -> Result<'a, (ValueMap<'a>, Comments)> {
+        let mut parser = Self::new(tokenizer, options);
+        parser.comments = Some(Comments::new());
+
+        let map = parser.parse_object(0)?;
+        Ok((map, parser.comments.unwrap_or_default()))
+    }
+
+    /// Like [`Parser::parse`], but also returns the mistakes that were worked around.
+    pub fn parse_with_warnings(
+        tokenizer: Tokenizer<'a>,
+        options: DecoderOptions,
+    ) -> Result<'a, (ValueMap<'a>, Vec<ParseWarning>)> {
+        let mut parser = Self::new(tokenizer, options);
+
+        let map = parser.parse_object(0)?;
+        Ok((map, parser.warnings))
+    }
+
+    /// Like [`Parser::parse`], but also returns some numbers about the document.
+    pub fn parse_with_stats(
+        tokenizer: Tokenizer<'a>,
+        options: DecoderOptions,
+    ) -> Result<'a, (ValueMap<'a>, ParseStats)> {
+        let lines = tokenizer.input().lines().count();
+        let mut parser = Self::new(tokenizer, options);
+
+        let map = parser.nested(|parser| parser.parse_object(0))?;
+        let stats = ParseStats {
+            lines,
+            ..parser.stats
+        };
+
+        Ok((map, stats))
+    }
+
+    /// Parses a whole document. That's usually an object, but a document holding nothing but
+    /// a single list or scalar, like `[1 2 3]`, is read as that value.
+    pub fn parse_document(
+        tokenizer: Tokenizer<'a>,
+        options: DecoderOptions,
+    ) -> Result<'a, HuonValue<'a>> {
+        Self::new(tokenizer, options).read_document()
+    }
+
+    /// Like [`Parser::parse_document`], but also keeps where each value is in the input.
+    /// Lists, braced objects and scalars span from their first to their last character,
+    /// nested objects from their first key to the end of their last value. An object that's the
+    /// whole document spans the whole input.
+    pub fn parse_spanned(
+        tokenizer: Tokenizer<'a>,
+        options: DecoderOptions,
+    ) -> Result<'a, Spanned<SpannedValue<'a>>> {
+        let end = tokenizer.input().len();
+        let mut parser = Self::new(tokenizer, options);
+        parser.spans = Some(HashMap::new());
+
+        let value = parser.read_document()?;
+        let mut spans = parser.spans.unwrap_or_default();
+        spans.entry(Vec::new()).or_insert(Span { start: 0, end });
+
+        Ok(spanned::build(value, &mut Vec::new(), &spans))
+    }
+
+    fn read_document(&mut self) -> Result<'a, HuonValue<'a>> {
+        // look ahead on a copy, so the object parser still sees the leading whitespace
+        match self.tokenizer.clone().significant().next().transpose()? {
+            None | Some(Token::Identifier(_)) => self.parse_object(0).map(HuonValue::Object),
+            Some(_) => {
+                self.skip_trivia()?;
+                let value = self.parse_inline_value()?;
+                self.skip_trivia()?;
+
+                // the rest isn't tokenized at all, it may not even be valid
+                match self.peek() {
+                    Some(_) if self.options.error_on_trailing_data => {
+                        Err(ParserError::TrailingData {
+                            start: self.span.start,
+                        })
+                    }
+                    _ => Ok(value),
+                }
+            }
+        }
+    }
+
+    /// A helper func to check if a token is indentation with the expected width.
     /// If found, it consumes the token and returns true.
     /// Otherwise, it returns false, or an error if the indentation is greater.
     fn check_indentation(&mut self, token: Token<'a>, expected_indent: usize) -> Result<'a, bool> {
-        if let Token::WhiteSpace(n) = token {
-            let indent = n / self.options.indent as usize;
+        if let Token::Indent(n) = token {
+            let indent = self.indent_level(n);
             match indent.cmp(&expected_indent) {
                 Ordering::Less => return Ok(false),
-                Ordering::Greater => return Err(ParserError::InvalidToken(token)),
+                Ordering::Greater => {
+                    return Err(ParserError::UnexpectedIndent {
+                        found: n,
+                        expected: expected_indent * self.options.indent as usize,
+                    })
+                }
                 Ordering::Equal => {
                     self.advance()?;
+                    self.warn_if_rounded(n);
                     return Ok(true);
                 }
             }
@@ -67,15 +337,55 @@ impl<'a> Parser<'a> {
         Ok(false)
     }
 
+    /// How many levels `n` spaces of indentation are. Usually that's rounded down,
+    /// but it's rounded to the nearest level with [`DecoderOptions::lenient_indent`].
+    fn indent_level(&self, n: usize) -> usize {
+        let indent = self.options.indent as usize;
+
+        if self.options.lenient_indent {
+            (n + indent / 2) / indent
+        } else {
+            n / indent
+        }
+    }
+
+    /// Records a warning if the indentation of a line that's being consumed was rounded.
+    fn warn_if_rounded(&mut self, n: usize) {
+        let indent = self.options.indent as usize;
+
+        if self.options.lenient_indent && !n.is_multiple_of(indent) {
+            self.warnings.push(ParseWarning::RoundedIndent {
+                found: n,
+                rounded: self.indent_level(n) * indent,
+            });
+        }
+    }
+
     fn parse_object(&mut self, expected_indent: usize) -> Result<'a, ValueMap<'a>> {
         let mut map = HashMap::new();
 
+        if expected_indent == 0 {
+            self.parse_flat_entries(&mut map)?;
+        }
+
         while let Some(Ok(token)) = self.peek() {
             if self.collapse > 0 {
                 self.collapse -= 1;
                 return Ok(map);
             }
 
+            if let Token::Comment(comment) = token {
+                self.advance()?;
+                self.pending_comments.push(comment);
+                continue;
+            }
+
+            // whitespace in the middle of a line isn't indentation
+            if let Token::WhiteSpace(_) = token {
+                self.advance()?;
+                continue;
+            }
+
             if let Token::NewLine = token {
                 self.advance()?;
 
@@ -85,15 +395,16 @@ impl<'a> Parser<'a> {
                 };
 
                 match next_token {
-                    Token::WhiteSpace(n)
-                        if expected_indent > 0
-                            && (n / self.options.indent as usize) < expected_indent =>
+                    Token::Indent(n)
+                        if expected_indent > 0 && self.indent_level(n) < expected_indent =>
                     {
-                        self.collapse = expected_indent - (n / self.options.indent as usize) - 1;
+                        self.collapse = expected_indent - self.indent_level(n) - 1;
                         return Ok(map);
                     }
 
-                    Token::Identifier(_) if expected_indent == 1 => {
+                    // back at the top level, close every nested object on the way
+                    Token::Identifier(_) if expected_indent > 0 => {
+                        self.collapse = expected_indent - 1;
                         return Ok(map);
                     }
 
@@ -105,52 +416,228 @@ impl<'a> Parser<'a> {
 
             let key = match self.advance()? {
                 Token::Identifier(s) => s,
-                token => return Err(ParserError::InvalidToken(token)),
+                token => return Err(ParserError::InvalidToken(token, self.span)),
             };
 
-            let value = match self.peek().unwrap()? {
-                Token::WhiteSpace(1) => {
-                    self.advance()?; // consume whitespace
+            self.stats.keys += 1;
+            self.attach_comments(key);
 
-                    if self.peek().unwrap()? == Token::ListStart {
-                        HuonValue::List(self.parse_list()?)
-                    } else {
-                        self.parse_value()?
-                    }
+            let value = self.at(PathSegment::Key(key), |parser| {
+                parser.parse_entry_value(key, expected_indent)
+            })?;
+            self.insert_entry(&mut map, key, value)?;
+        }
+
+        Ok(map)
+    }
+
+    /// Parses the top-level `key: value` lines of a document for as long as there's nothing
+    /// else, skipping the indentation and comment bookkeeping of [`Parser::parse_object`].
+    /// Stops after the first nested object or at anything it doesn't handle, like a comment,
+    /// and leaves the rest of the document to the full loop.
+    fn parse_flat_entries(&mut self, map: &mut ValueMap<'a>) -> Result<'a, ()> {
+        loop {
+            match self.peek().transpose()? {
+                Some(Token::NewLine) => {
+                    self.advance()?;
                 }
 
-                Token::NewLine => {
+                Some(Token::Identifier(key)) => {
                     self.advance()?;
+                    self.stats.keys += 1;
+
+                    let flat = matches!(
+                        self.peek().transpose()?,
+                        Some(Token::WhiteSpace(_) | Token::ObjectStart)
+                    );
+                    let value = self.at(PathSegment::Key(key), |parser| {
+                        parser.parse_entry_value(key, 0)
+                    })?;
+                    self.insert_entry(map, key, value)?;
+
+                    if !flat {
+                        return Ok(());
+                    }
+                }
+
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Parses what follows `key`, either a value on the same line or one indented below,
+    /// usually an object.
+    fn parse_entry_value(
+        &mut self,
+        key: &'a str,
+        expected_indent: usize,
+    ) -> Result<'a, HuonValue<'a>> {
+        Ok(match self.peek().ok_or(ParserError::Eof)?? {
+            // values may be padded to line up, see `to_string_pretty`
+            Token::WhiteSpace(_) => {
+                self.advance()?; // consume whitespace
+                self.parse_inline_value()?
+            }
+
+            // `key:{x: 1}`, braces don't need the space
+            Token::ObjectStart => self.parse_inline_value()?,
+
+            // `key: # note` is followed by the value on the next line, the note is dropped
+            Token::Comment(_) => {
+                self.advance()?;
+                self.parse_entry_value(key, expected_indent)?
+            }
+
+            Token::NewLine => {
+                self.advance()?;
+                self.collect_comment_lines()?;
+
+                match self.peek().ok_or(ParserError::Eof)?? {
+                    Token::Indent(n) if self.indent_level(n) > expected_indent => {
+                        self.advance()?;
+                        self.warn_if_rounded(n);
+
+                        // a single value on its own line, instead of the object's first key
+                        if !matches!(self.peek().transpose()?, Some(Token::Identifier(_))) {
+                            return self.parse_inline_value();
+                        }
+
+                        let start = self.span.start;
+                        self.path.push(key);
+                        let level = self.indent_level(n);
+                        let object = self.nested(|parser| parser.parse_object(level))?;
+                        self.path.pop();
+                        self.record_span(start);
+
+                        HuonValue::Object(object)
+                    }
+
+                    // see `DecoderOptions::allow_hanging_indent`
+                    token @ (Token::Indent(_) | Token::Identifier(_))
+                        if self.options.allow_hanging_indent =>
+                    {
+                        let level = match token {
+                            Token::Indent(n) => {
+                                self.advance()?;
+                                self.warn_if_rounded(n);
+                                self.indent_level(n)
+                            }
+                            _ => 0,
+                        };
+
+                        let start = self.peek().map_or(0, |_| self.span.start);
+                        self.path.push(key);
+                        let object = self.nested(|parser| parser.parse_object(level))?;
+                        self.path.pop();
+                        self.record_span(start);
 
-                    match self.peek().unwrap()? {
-                        Token::WhiteSpace(n)
-                            if (n / self.options.indent as usize) > expected_indent =>
-                        {
-                            self.advance()?;
-                            HuonValue::Object(self.parse_object(n / self.options.indent as usize)?)
+                        // the object only ends at a line indented less than itself, so the one
+                        // holding `key`, which isn't indented less, ends there too
+                        if self.peek().is_some() {
+                            self.collapse += expected_indent - level + 1;
                         }
-                        token => return Err(ParserError::InvalidToken(token)),
+
+                        HuonValue::Object(object)
                     }
+
+                    token => return Err(ParserError::InvalidToken(token, self.span)),
                 }
+            }
 
-                token => return Err(ParserError::InvalidToken(token)),
-            };
+            token => return Err(ParserError::InvalidToken(token, self.span)),
+        })
+    }
 
-            map.insert(key, value);
+    /// Adds an entry to `map`, handling a key it already has according to
+    /// [`DecoderOptions::duplicate_keys`].
+    fn insert_entry(
+        &self,
+        map: &mut ValueMap<'a>,
+        key: &'a str,
+        value: HuonValue<'a>,
+    ) -> Result<'a, ()> {
+        let mut entry = match map.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                return Ok(());
+            }
+            Entry::Occupied(entry) => entry,
+        };
+
+        match self.options.duplicate_keys {
+            DuplicateKeyMode::LastWins => *entry.get_mut() = value,
+            DuplicateKeyMode::Error => return Err(ParserError::DuplicateKey(key)),
+            DuplicateKeyMode::AppendLists => match (entry.get_mut(), value) {
+                (HuonValue::List(list), HuonValue::List(more)) => list.extend(more),
+                (old, value) => *old = value,
+            },
         }
 
-        Ok(map)
+        Ok(())
+    }
+
+    /// Consumes whitespace, line breaks and comments.
+    /// Stops in front of a token the tokenizer failed on, leaving its error to the caller.
+    fn skip_trivia(&mut self) -> Result<'a, ()> {
+        while let Some(Ok(
+            Token::WhiteSpace(_) | Token::Indent(_) | Token::NewLine | Token::Comment(_),
+        )) = self.peek()
+        {
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    /// Consumes lines holding nothing but a comment, so the line after them can be looked at.
+    fn collect_comment_lines(&mut self) -> Result<'a, ()> {
+        while let Some(Token::Comment(comment)) = self.peek().transpose()? {
+            self.advance()?;
+            self.pending_comments.push(comment);
+
+            if let Some(Token::NewLine) = self.peek().transpose()? {
+                self.advance()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands the pending comments to `key`, if comments are being kept at all.
+    fn attach_comments(&mut self, key: &'a str) {
+        if self.pending_comments.is_empty() {
+            return;
+        }
+
+        let lines = self
+            .pending_comments
+            .drain(..)
+            .map(str::to_string)
+            .collect();
+
+        if let Some(comments) = &mut self.comments {
+            let path = self
+                .path
+                .iter()
+                .chain([&key])
+                .map(ToString::to_string)
+                .collect();
+            comments.insert(path, lines);
+        }
     }
 
     fn parse_value(&mut self) -> Result<'a, HuonValue<'a>> {
         // This function will try to parse a literal value.
         Ok(match self.advance()? {
-            Token::Str(s) => HuonValue::String(s),
-            Token::Int(i) => HuonValue::Int(i),
+            Token::Str(s) => HuonValue::from(s),
+            Token::Int(i) | Token::RawInt { value: i, .. } => HuonValue::Int(i),
+            Token::BigInt(i) => HuonValue::BigInt(i),
             Token::Boolean(b) => HuonValue::Boolean(b),
-            Token::Float(f) => HuonValue::Float(f),
+            Token::Float(f) | Token::RawFloat { value: f, .. } => HuonValue::Float(f),
+            Token::Percent(p) => HuonValue::Float(scalar::percent_to_fraction(p)),
+            Token::Block { text, indent } => {
+                HuonValue::String(Cow::Owned(block_text(text, indent)))
+            }
             Token::Null => HuonValue::Null,
-            token => return Err(ParserError::InvalidToken(token)),
+            token => return Err(ParserError::InvalidToken(token, self.span)),
         })
     }
 
@@ -158,15 +645,20 @@ impl<'a> Parser<'a> {
         let mut list = Vec::new();
 
         self.advance()?; // consume ListStart
+        let start = self.span.start;
 
-        while let Some(Ok(token)) = self.peek() {
-            match token {
+        loop {
+            let Some(token) = self.peek() else {
+                return Err(ParserError::UnterminatedList { start });
+            };
+
+            match token? {
                 Token::ListEnd => {
                     self.advance()?; // consume ListEnd
                     break;
                 }
 
-                Token::WhiteSpace(_) | Token::NewLine => {
+                Token::WhiteSpace(_) | Token::Indent(_) | Token::NewLine | Token::Comment(_) => {
                     self.advance()?; // consume whitespace
                 }
 
@@ -175,7 +667,8 @@ impl<'a> Parser<'a> {
                 }
 
                 _ => {
-                    let value = self.parse_value()?;
+                    let value =
+                        self.at(PathSegment::Index(list.len()), Self::parse_inline_value)?;
                     list.push(value);
                 }
             }
@@ -184,24 +677,236 @@ impl<'a> Parser<'a> {
         Ok(list)
     }
 
+    /// Parses a value that is written on a single line, or a list/braced object spanning multiple.
+    fn parse_inline_value(&mut self) -> Result<'a, HuonValue<'a>> {
+        let token = self.peek().ok_or(ParserError::Eof)??;
+        let start = self.span.start;
+
+        let value = match token {
+            Token::ListStart => HuonValue::List(self.nested(Self::parse_list)?),
+            Token::ObjectStart => HuonValue::Object(self.nested(Self::parse_inline_object)?),
+            _ => self.parse_value()?,
+        };
+
+        // the value's last token was the last one consumed
+        self.value_end = self.span.end;
+        self.record_span(start);
+        Ok(value)
+    }
+
+    /// Parses a braced object like `{ name: "IT" }`. Indentation doesn't matter in here,
+    /// entries may be separated by whitespace, newlines or commas.
+    fn parse_inline_object(&mut self) -> Result<'a, ValueMap<'a>> {
+        let mut map = HashMap::new();
+
+        self.advance()?; // consume ObjectStart
+
+        loop {
+            match self.peek().ok_or(ParserError::Eof)?? {
+                Token::ObjectEnd => {
+                    self.advance()?; // consume ObjectEnd
+                    return Ok(map);
+                }
+
+                Token::WhiteSpace(_)
+                | Token::Indent(_)
+                | Token::NewLine
+                | Token::Separator
+                | Token::Comment(_) => {
+                    self.advance()?;
+                }
+
+                Token::Identifier(key) => {
+                    self.advance()?;
+                    self.stats.keys += 1;
+
+                    if let Some(Token::WhiteSpace(_)) = self.peek().transpose()? {
+                        self.advance()?;
+                    }
+
+                    let value = self.at(PathSegment::Key(key), Self::parse_inline_value)?;
+                    self.insert_entry(&mut map, key, value)?;
+                }
+
+                token => return Err(ParserError::InvalidToken(token, self.span)),
+            }
+        }
+    }
+
     fn peek(&mut self) -> Option<Result<'a, Token<'a>>> {
-        self.tokenizer.peek().map(|res| res.clone().map_err(Into::into))
+        if self.peeked.is_none() {
+            self.peeked = self.next_token();
+        }
+
+        let (span, token) = self.peeked.as_ref()?;
+        self.span = *span;
+        Some(token.clone().map_err(Into::into))
     }
 
     fn advance(&mut self) -> Result<'a, Token<'a>> {
-        self.tokenizer.next().unwrap().map_err(Into::into)
+        self.stats.tokens += 1;
+
+        let (span, token) = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.next_token().unwrap(),
+        };
+        self.span = span;
+        token.map_err(Into::into)
+    }
+
+    /// Reads the next token from the tokenizer, along with where it is.
+    fn next_token(&mut self) -> Option<(Span, std::result::Result<Token<'a>, TokenizerError>)> {
+        let start = self.tokenizer.offset();
+        let token = self.tokenizer.next()?;
+        let end = self.tokenizer.offset();
+
+        Some((Span { start, end }, token))
     }
+
+    /// Runs `parse` one object or list deeper, keeping track of the maximum depth.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<'a, T>) -> Result<'a, T> {
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Runs `parse` for the value at `segment`, inside the current one.
+    fn at<T>(
+        &mut self,
+        segment: PathSegment<'a>,
+        parse: impl FnOnce(&mut Self) -> Result<'a, T>,
+    ) -> Result<'a, T> {
+        self.value_path.push(segment);
+        let result = parse(self);
+        self.value_path.pop();
+        result
+    }
+
+    /// Records that the current value goes from `start` to the end of the last one parsed,
+    /// if spans are being kept.
+    fn record_span(&mut self, start: usize) {
+        if let Some(spans) = &mut self.spans {
+            let span = Span {
+                start,
+                end: self.value_end,
+            };
+            spans.insert(self.value_path.clone(), span);
+        }
+    }
+}
+
+/// The value of a block scalar, its lines without their common `indent`, each ending in a
+/// line break.
+fn block_text(text: &str, indent: usize) -> String {
+    let mut block = String::with_capacity(text.len() + 1);
+
+    for line in text.lines() {
+        block.push_str(line.get(indent..).unwrap_or_default());
+        block.push('\n');
+    }
+    block
 }
 
 pub fn parse(
     input: &str,
     options: DecoderOptions,
 ) -> std::result::Result<ValueMap<'_>, ParserError<'_>> {
-    let tokenizer = crate::tokenizer::Tokenizer::new(input);
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
 
     Parser::parse(tokenizer, options)
 }
 
+/// Parses a stream of documents separated by lines holding just `---`, each one like [`parse`].
+/// The `---` has to be indented by [`DecoderOptions::base_indent`], one in a block scalar is
+/// part of its text. The spans in errors are relative to the start of the document they're in.
+pub fn parse_many(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<Vec<ValueMap<'_>>, ParserError<'_>> {
+    let leading_spaces = |line: &str| line.len() - line.trim_start_matches(' ').len();
+
+    let mut documents = Vec::new();
+    let mut document_start = 0;
+    let mut line_end = 0;
+    // the indentation of the line the block scalar we're in starts on
+    let mut block_indent = None;
+
+    for line in input.split_inclusive('\n') {
+        let line_start = line_end;
+        line_end += line.len();
+
+        let text = line.trim_end();
+        let indent = leading_spaces(text);
+
+        if block_indent.is_some_and(|block_indent| text.is_empty() || indent > block_indent) {
+            continue;
+        }
+        block_indent = text.ends_with('|').then_some(indent);
+
+        if indent == options.base_indent && text.trim_start() == "---" {
+            documents.push(parse(&input[document_start..line_start], options)?);
+            document_start = line_end;
+        }
+    }
+
+    documents.push(parse(&input[document_start..], options)?);
+    Ok(documents)
+}
+
+/// Parses `input` like [`parse`], also returning the mistakes that were worked around.
+pub fn parse_with_warnings(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<(ValueMap<'_>, Vec<ParseWarning>), ParserError<'_>> {
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
+
+    Parser::parse_with_warnings(tokenizer, options)
+}
+
+/// Parses `input` like [`parse`], also returning some numbers about the document.
+pub fn parse_with_stats(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<(ValueMap<'_>, ParseStats), ParserError<'_>> {
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
+
+    Parser::parse_with_stats(tokenizer, options)
+}
+
+/// Parses `input` as a whole document, see [`Parser::parse_document`].
+pub fn parse_document(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<HuonValue<'_>, ParserError<'_>> {
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
+
+    Parser::parse_document(tokenizer, options)
+}
+
+/// Parses `input` as a whole document, keeping where each value is, see
+/// [`Parser::parse_spanned`].
+pub fn parse_spanned(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<Spanned<SpannedValue<'_>>, ParserError<'_>> {
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
+
+    Parser::parse_spanned(tokenizer, options)
+}
+
+/// Parses `input` like [`parse`], also returning the comments written above each key.
+pub fn parse_with_comments(
+    input: &str,
+    options: DecoderOptions,
+) -> std::result::Result<(ValueMap<'_>, Comments), ParserError<'_>> {
+    let tokenizer = crate::tokenizer::Tokenizer::with_options(input, options);
+
+    Parser::parse_with_comments(tokenizer, options)
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -264,36 +969,36 @@ mod tests {
         let map = parse(include_str!("../../test.huon"), DecoderOptions::default())?;
 
         let expected = map! {
-            "name" => HuonValue::String("John"),
+            "name" => HuonValue::String("John".into()),
             "job1" => HuonValue::Object(map! {
                 "category" => HuonValue::Object(map! {
-                    "name" => HuonValue::String("IT")
+                    "name" => HuonValue::String("IT".into())
                 }),
                 "info" => HuonValue::Object(map! {
                     "pay" => HuonValue::Float(-4200.5),
                     "payrate" => HuonValue::Object(map! {
-                        "iteration" => HuonValue::String("monthly"),
-                        "date" => HuonValue::String("Last Friday of every month"),
-                        "monthly_increase" => HuonValue::String("5%")
+                        "iteration" => HuonValue::String("monthly".into()),
+                        "date" => HuonValue::String("Last Friday of every month".into()),
+                        "monthly_increase" => HuonValue::String("5%".into())
                     })
                 }),
-                "name" => HuonValue::String("Software Engineer")
+                "name" => HuonValue::String("Software Engineer".into())
             }),
             "age" => HuonValue::Int(32),
             "job2" => HuonValue::Object(map! {
                 "category" => HuonValue::Object(map! {
-                    "name" => HuonValue::String("Security")
+                    "name" => HuonValue::String("Security".into())
                 }),
                 "info" => HuonValue::Object(map! {
                     "pay" => HuonValue::Int(3700), // treated as an int here because the parser/tokenizer does not find an integer
                     "payrate" => HuonValue::Object(map! {
-                        "iteration" => HuonValue::String("weekly"),
-                        "date" => HuonValue::String("Every Friday")
+                        "iteration" => HuonValue::String("weekly".into()),
+                        "date" => HuonValue::String("Every Friday".into())
                     })
                 }),
-                "name" => HuonValue::String("Bodyguard")
+                "name" => HuonValue::String("Bodyguard".into())
             }),
-            "last_name" => HuonValue::String("Doe")
+            "last_name" => HuonValue::String("Doe".into())
         };
 
         assert_eq!(map, expected);
@@ -306,6 +1011,889 @@ mod tests {
         let err =
             parse("1job1: \"swe\"", DecoderOptions::default()).unwrap_err();
 
-        assert_eq!(err, ParserError::InvalidToken(Token::Int(1)));
+        assert_eq!(
+            err,
+            ParserError::TokenizerError(TokenizerError::InvalidIdentifier("1job1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parser_tab_indentation() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = "job1:\n\tinfo:\n\t\tpay: 1\n\tname: \"Software Engineer\"\nage: 32";
+        let options = DecoderOptions {
+            tab_width: Some(4),
+            ..Default::default()
+        };
+
+        let map = parse(input, options)?;
+
+        let expected = map! {
+            "job1" => HuonValue::Object(map! {
+                "info" => HuonValue::Object(map! {
+                    "pay" => HuonValue::Int(1)
+                }),
+                "name" => HuonValue::String("Software Engineer".into())
+            }),
+            "age" => HuonValue::Int(32)
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_escaped_keyword_key() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse("`true`: 1\nflag: true", DecoderOptions::default())?;
+
+        let expected = map! {
+            "true" => HuonValue::Int(1),
+            "flag" => HuonValue::Boolean(true)
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_keywords_as_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse(
+            "true: 1\nfalse: 2\nnull: 3\nvalue: true",
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "true" => HuonValue::Int(1),
+            "false" => HuonValue::Int(2),
+            "null" => HuonValue::Int(3),
+            "value" => HuonValue::Boolean(true)
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_keyword_values() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse("a: true\nb: false\nc: null", DecoderOptions::default())?;
+
+        let expected = map! {
+            "a" => HuonValue::Boolean(true),
+            "b" => HuonValue::Boolean(false),
+            "c" => HuonValue::Null
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_inline_objects() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse(
+            indoc! {r#"
+                category: { name: "IT", tags: [1 2] }
+                jobs: [
+                    {
+                        name: "a"
+                        info: { pay: 1 }
+                    }
+                    { name: "b" }
+                ]"#},
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "category" => HuonValue::Object(map! {
+                "name" => HuonValue::String("IT".into()),
+                "tags" => HuonValue::List(vec![HuonValue::Int(1), HuonValue::Int(2)])
+            }),
+            "jobs" => HuonValue::List(vec![
+                HuonValue::Object(map! {
+                    "name" => HuonValue::String("a".into()),
+                    "info" => HuonValue::Object(map! {
+                        "pay" => HuonValue::Int(1)
+                    })
+                }),
+                HuonValue::Object(map! {
+                    "name" => HuonValue::String("b".into())
+                })
+            ])
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_inline_object_spacing() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let expected = map! {
+            "point" => HuonValue::Object(map! {
+                "x" => HuonValue::Int(1)
+            }),
+            "name" => HuonValue::String("origin".into())
+        };
+
+        for input in [
+            "point:{x:1}\nname: \"origin\"",
+            "point: {x: 1}\nname: \"origin\"",
+        ] {
+            assert_eq!(parse(input, DecoderOptions::default())?, expected);
+        }
+
+        let map = parse("outer:\n    point:{x:1}", DecoderOptions::default())?;
+        assert_eq!(
+            map,
+            map! {
+                "outer" => HuonValue::Object(map! {
+                    "point" => HuonValue::Object(map! {
+                        "x" => HuonValue::Int(1)
+                    })
+                })
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_unterminated_list() {
+        let input = "name: \"John\"\nitems: [1 2 3";
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(err, ParserError::UnterminatedList { start: 20 });
+        assert_eq!(err.span(), Some(Span { start: 20, end: 21 }));
+
+        let err = parse("items: [[1 2]\n", DecoderOptions::default()).unwrap_err();
+        assert_eq!(err, ParserError::UnterminatedList { start: 7 });
+    }
+
+    #[test]
+    fn test_parser_unterminated_inline_object() {
+        let err = parse("category: { name: \"IT\"", DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(err, ParserError::Eof);
+    }
+
+    #[test]
+    fn test_parser_dedent_to_top_level() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse(
+            indoc! {"
+                a:
+                    b:
+                        c: 1
+                d: 2"},
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "a" => HuonValue::Object(map! {
+                "b" => HuonValue::Object(map! {
+                    "c" => HuonValue::Int(1)
+                })
+            }),
+            "d" => HuonValue::Int(2)
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_comments() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let (map, comments) = parse_with_comments(
+            indoc! {"
+                # who
+                # they are
+                name: \"John\"
+                job:
+                    # per month
+                    pay: 1
+                    codes: [1 # first
+                        2]
+                # top level again
+                age: 32"},
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "name" => HuonValue::String("John".into()),
+            "job" => HuonValue::Object(map! {
+                "pay" => HuonValue::Int(1),
+                "codes" => HuonValue::List(vec![HuonValue::Int(1), HuonValue::Int(2)])
+            }),
+            "age" => HuonValue::Int(32)
+        };
+
+        let path = |keys: &[&str]| keys.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let expected_comments = map! {
+            path(&["name"]) => vec!["who".to_string(), "they are".to_string()],
+            path(&["job", "pay"]) => vec!["per month".to_string()],
+            path(&["age"]) => vec!["top level again".to_string()]
+        };
+
+        assert_eq!(map, expected);
+        assert_eq!(comments, expected_comments);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_extended_numbers() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let map = parse(
+            "list: [1e3 2_000 0xFF]\nsci: 1e3\nunderscore: 2_000\nhex: 0xFF",
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "list" => HuonValue::List(vec![
+                HuonValue::Float(1000.0),
+                HuonValue::Int(2000),
+                HuonValue::Int(255),
+            ]),
+            "sci" => HuonValue::Float(1000.0),
+            "underscore" => HuonValue::Int(2000),
+            "hex" => HuonValue::Int(255)
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_bareword_as_string() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = "color: red\ntags: [dark \"light\" true]";
+        let options = DecoderOptions {
+            bareword_as_string: true,
+            ..Default::default()
+        };
+
+        let map = parse(input, options)?;
+
+        let expected = map! {
+            "color" => HuonValue::String("red".into()),
+            "tags" => HuonValue::List(vec![
+                HuonValue::String("dark".into()),
+                HuonValue::String("light".into()),
+                HuonValue::Boolean(true),
+            ])
+        };
+
+        assert_eq!(map, expected);
+
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(
+            err,
+            ParserError::TokenizerError(TokenizerError::InvalidIdentifier("red".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_top_level_values() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let list = parse_document("\n# codes\n[1 2\n    3]\n", DecoderOptions::default())?;
+        assert_eq!(
+            list,
+            HuonValue::List(vec![
+                HuonValue::Int(1),
+                HuonValue::Int(2),
+                HuonValue::Int(3),
+            ])
+        );
+
+        let scalar = parse_document("  \"John\"", DecoderOptions::default())?;
+        assert_eq!(scalar, HuonValue::String("John".into()));
+
+        let object = parse_document("# person\nage: 32", DecoderOptions::default())?;
+        assert_eq!(
+            object,
+            HuonValue::Object(map! { "age" => HuonValue::Int(32) })
+        );
+
+        let empty = parse_document("", DecoderOptions::default())?;
+        assert_eq!(empty, HuonValue::Object(HashMap::new()));
+
+        let err = parse_document("[1 2] 3", DecoderOptions::default()).unwrap_err();
+        assert_eq!(err, ParserError::TrailingData { start: 6 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_unexpected_indent() {
+        let input = indoc! {"
+            job:
+                name: 1
+                    pay: 2"};
+
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(
+            err,
+            ParserError::UnexpectedIndent {
+                found: 8,
+                expected: 4
+            }
+        );
+        assert_eq!(err.to_string(), "Found 8 spaces of indentation, expected 4");
+
+        let err = parse("    name: 1", DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(
+            err,
+            ParserError::UnexpectedIndent {
+                found: 4,
+                expected: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_parser_lenient_indent() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = "job:\n   name: 1\n    pay:\n         amount: 2\nage: 3";
+        let options = DecoderOptions {
+            lenient_indent: true,
+            ..Default::default()
+        };
+
+        let (map, warnings) = parse_with_warnings(input, options)?;
+
+        let expected = map! {
+            "job" => HuonValue::Object(map! {
+                "name" => HuonValue::Int(1),
+                "pay" => HuonValue::Object(map! {
+                    "amount" => HuonValue::Int(2)
+                })
+            }),
+            "age" => HuonValue::Int(3)
+        };
+
+        assert_eq!(map, expected);
+        assert_eq!(
+            warnings,
+            [
+                ParseWarning::RoundedIndent {
+                    found: 3,
+                    rounded: 4
+                },
+                ParseWarning::RoundedIndent {
+                    found: 9,
+                    rounded: 8
+                },
+            ]
+        );
+
+        assert!(parse(input, DecoderOptions::default()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_stats() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = include_str!("../../test.huon");
+
+        let (map, stats) = parse_with_stats(input, DecoderOptions::default())?;
+
+        assert_eq!(map, parse(input, DecoderOptions::default())?);
+        assert_eq!(stats.tokens, Tokenizer::new(input).count());
+        assert_eq!(
+            stats,
+            ParseStats {
+                tokens: 89,
+                keys: 22,
+                max_depth: 4,
+                lines: 22
+            }
+        );
+
+        let (_, stats) = parse_with_stats("a: [[1] { b: 2 }]", DecoderOptions::default())?;
+
+        assert_eq!(stats.keys, 2);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.lines, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_flat_then_nested() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {"
+            a: 1
+
+            b: [1 2]
+            c:
+                d: true
+            e: null
+            # a comment
+            f: \"x\"
+        "};
+
+        let map = parse(input, DecoderOptions::default())?;
+
+        let expected = map! {
+            "a" => HuonValue::Int(1),
+            "b" => HuonValue::List(vec![HuonValue::Int(1), HuonValue::Int(2)]),
+            "c" => HuonValue::Object(map! {
+                "d" => HuonValue::Boolean(true)
+            }),
+            "e" => HuonValue::Null,
+            "f" => HuonValue::String("x".into())
+        };
+
+        assert_eq!(map, expected);
+
+        let map = parse(
+            include_str!("../../test_flat.huon"),
+            DecoderOptions::default(),
+        )?;
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map["key_1"], HuonValue::Int(7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_duplicate_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = "items: [1 2]\nname: \"a\"\nitems: [3]\nname: \"b\"";
+
+        let map = parse(input, DecoderOptions::default())?;
+        assert_eq!(map["items"], HuonValue::List(vec![HuonValue::Int(3)]));
+        assert_eq!(map["name"], HuonValue::String("b".into()));
+
+        let options = DecoderOptions {
+            duplicate_keys: DuplicateKeyMode::AppendLists,
+            ..Default::default()
+        };
+        let map = parse(input, options)?;
+        assert_eq!(
+            map["items"],
+            HuonValue::List(vec![
+                HuonValue::Int(1),
+                HuonValue::Int(2),
+                HuonValue::Int(3),
+            ])
+        );
+        assert_eq!(map["name"], HuonValue::String("b".into()));
+
+        let options = DecoderOptions {
+            duplicate_keys: DuplicateKeyMode::Error,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse("obj: { a: 1, a: 2 }", options).unwrap_err(),
+            ParserError::DuplicateKey("a")
+        );
+        assert_eq!(
+            parse(input, options).unwrap_err(),
+            ParserError::DuplicateKey("items")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_comment_after_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {"
+            items: [1 2 3] # the items
+            nested:
+                more: [
+                    4
+                ]   # closing
+            last: [] #
+        "};
+
+        let map = parse(input, DecoderOptions::default())?;
+
+        let expected = map! {
+            "items" => HuonValue::List(vec![
+                HuonValue::Int(1),
+                HuonValue::Int(2),
+                HuonValue::Int(3),
+            ]),
+            "nested" => HuonValue::Object(map! {
+                "more" => HuonValue::List(vec![HuonValue::Int(4)])
+            }),
+            "last" => HuonValue::List(vec![])
+        };
+
+        assert_eq!(map, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_adjacent_list_entries() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        // strings, lists and braced objects end on a delimiter, so no separator is needed
+        let map = parse(
+            "keywords: [null true false]\nstrings: [\"a\"\"b\"]\nmixed: [\"a\"1[2]{x: 3}null\"b\"]",
+            DecoderOptions::default(),
+        )?;
+
+        let expected = map! {
+            "keywords" => HuonValue::List(vec![
+                HuonValue::Null,
+                HuonValue::Boolean(true),
+                HuonValue::Boolean(false),
+            ]),
+            "strings" => HuonValue::List(vec![HuonValue::String("a".into()), HuonValue::String("b".into())]),
+            "mixed" => HuonValue::List(vec![
+                HuonValue::String("a".into()),
+                HuonValue::Int(1),
+                HuonValue::List(vec![HuonValue::Int(2)]),
+                HuonValue::Object(map! { "x" => HuonValue::Int(3) }),
+                HuonValue::Null,
+                HuonValue::String("b".into()),
+            ])
+        };
+
+        assert_eq!(map, expected);
+
+        // words and numbers run into each other though
+        for (input, word) in [("a: [truefalse]", "truefalse"), ("a: [1.5true]", "1.5true")] {
+            assert_eq!(
+                parse(input, DecoderOptions::default()).unwrap_err(),
+                ParserError::TokenizerError(TokenizerError::InvalidIdentifier(word.to_string()))
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_base_indent() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = "    name: \"John\"\n    job:\n        pay: 4200\n\n    age: 32\n";
+        let options = DecoderOptions {
+            base_indent: 4,
+            ..Default::default()
+        };
+
+        let map = parse(input, options)?;
+
+        let expected = map! {
+            "name" => HuonValue::String("John".into()),
+            "job" => HuonValue::Object(map! {
+                "pay" => HuonValue::Int(4200)
+            }),
+            "age" => HuonValue::Int(32)
+        };
+
+        assert_eq!(map, expected);
+        assert!(parse(input, DecoderOptions::default()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_lone_minus() {
+        let err = parse("numbers: [- 1]", DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(
+            err,
+            ParserError::TokenizerError(TokenizerError::MalformedNumber("-".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parser_error_context() {
+        let input = "name: \"John\"\nage: 3 3\n";
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+
+        assert_eq!(err.span(), Some(Span { start: 20, end: 21 }));
+        assert_eq!(
+            err.with_context(input),
+            indoc! {"
+                Invalid token: Int(3)
+                 --> 2:8
+                  |
+                2 | age: 3 3
+                  |        ^"}
+        );
+
+        let input = "a: 1\nb: \"open\nc: 2";
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+        assert_eq!(
+            err.with_context(input),
+            indoc! {"
+                The string starting at byte 8 is never closed
+                 --> 2:4
+                  |
+                2 | b: \"open
+                  |    ^"}
+        );
+
+        let err = ParserError::Eof;
+        assert_eq!(err.with_context(""), "EOF");
+    }
+
+    #[cfg(feature = "codespan")]
+    #[test]
+    fn test_parser_error_diagnostic() {
+        use codespan_reporting::{
+            files::SimpleFile,
+            term::{self, Config},
+        };
+
+        let input = "name: \"John\"\nlast_name: \"Doe";
+        let err = parse(input, DecoderOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::TokenizerError(TokenizerError::UnterminatedString { start: 24 })
+        ));
+
+        let diagnostic = err.to_diagnostic(());
+        assert_eq!(
+            diagnostic.message,
+            "The string starting at byte 24 is never closed"
+        );
+        assert_eq!(diagnostic.labels[0].range, 24..25);
+
+        let file = SimpleFile::new("test.huon", input);
+        let rendered = term::emit_into_string(&Config::default(), &file, &diagnostic).unwrap();
+        assert!(rendered.contains("test.huon:2:12"));
+    }
+
+    #[test]
+    fn test_parser_parse_many() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {r#"
+            name: "api"
+            port: 8080
+            ---
+            name: "db"
+            settings:
+                port: 5432
+        "#};
+
+        let documents = parse_many(input, DecoderOptions::default())?;
+
+        assert_eq!(
+            documents,
+            vec![
+                map! {
+                    "name" => HuonValue::String("api".into()),
+                    "port" => HuonValue::Int(8080)
+                },
+                map! {
+                    "name" => HuonValue::String("db".into()),
+                    "settings" => HuonValue::Object(map! {
+                        "port" => HuonValue::Int(5432)
+                    })
+                },
+            ]
+        );
+
+        let err = parse_many("a: 1\n---\nb: 2 2", DecoderOptions::default()).unwrap_err();
+        assert!(matches!(err, ParserError::InvalidToken(Token::Int(2), _)));
+
+        let input = "run: |\n    echo\n    ---\n\n    ---\n---\nrun: 1\n";
+        let documents = parse_many(input, DecoderOptions::default())?;
+        assert_eq!(
+            documents,
+            vec![
+                map! { "run" => HuonValue::String("echo\n---\n\n---\n".into()) },
+                map! { "run" => HuonValue::Int(1) },
+            ]
+        );
+
+        let options = DecoderOptions {
+            base_indent: 2,
+            ..Default::default()
+        };
+        let documents = parse_many("  a: 1\n  ---\n  b: 2\n", options)?;
+        assert_eq!(
+            documents,
+            vec![
+                map! { "a" => HuonValue::Int(1) },
+                map! { "b" => HuonValue::Int(2) }
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_comment_before_value() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {r#"
+            name: # temporary
+                "John"
+            job:
+                title: # keep it short
+                    "SWE"
+                level: 3
+            age: 32
+        "#};
+
+        let map = parse(input, DecoderOptions::default())?;
+
+        let expected = map! {
+            "name" => HuonValue::String("John".into()),
+            "job" => HuonValue::Object(map! {
+                "title" => HuonValue::String("SWE".into()),
+                "level" => HuonValue::Int(3)
+            }),
+            "age" => HuonValue::Int(32)
+        };
+
+        assert_eq!(map, expected);
+
+        let err = parse("name: # nothing after", DecoderOptions::default()).unwrap_err();
+        assert_eq!(err, ParserError::Eof);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_hanging_indent() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let options = DecoderOptions {
+            allow_hanging_indent: true,
+            ..Default::default()
+        };
+
+        let input = indoc! {r#"
+            jobs:
+                job1:
+                title: "SWE"
+                pay: 1
+            name: "John"
+        "#};
+
+        let expected = map! {
+            "jobs" => HuonValue::Object(map! {
+                "job1" => HuonValue::Object(map! {
+                    "title" => HuonValue::String("SWE".into()),
+                    "pay" => HuonValue::Int(1)
+                })
+            }),
+            "name" => HuonValue::String("John".into())
+        };
+
+        assert_eq!(parse(input, options)?, expected);
+        assert!(parse(input, DecoderOptions::default()).is_err());
+
+        let input = indoc! {r#"
+            person:
+            name: "John"
+            age: 32
+        "#};
+
+        let expected = map! {
+            "person" => HuonValue::Object(map! {
+                "name" => HuonValue::String("John".into()),
+                "age" => HuonValue::Int(32)
+            })
+        };
+
+        assert_eq!(parse(input, options)?, expected);
+
+        let input = indoc! {"
+            a:
+                b:
+                    c:
+                    d: 1
+                e: 2
+        "};
+
+        let expected = map! {
+            "a" => HuonValue::Object(map! {
+                "b" => HuonValue::Object(map! {
+                    "c" => HuonValue::Object(map! {
+                        "d" => HuonValue::Int(1)
+                    })
+                }),
+                "e" => HuonValue::Int(2)
+            })
+        };
+
+        assert_eq!(parse(input, options)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_spanned() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {r#"
+            name: "John"
+            job:
+                title: "swe"
+                codes: [1 {x: 2.5}]
+        "#};
+
+        let spanned = HuonValue::parse_spanned(input, DecoderOptions::default())?;
+        assert_eq!(
+            spanned.span,
+            Span {
+                start: 0,
+                end: input.len()
+            }
+        );
+
+        let span_of =
+            |spanned: &Spanned<SpannedValue>| &input[spanned.span.start..spanned.span.end];
+
+        let name = spanned.value.get("name").unwrap();
+        assert_eq!(span_of(name), "\"John\"");
+
+        let job = spanned.value.get("job").unwrap();
+        assert_eq!(span_of(job), "title: \"swe\"\n    codes: [1 {x: 2.5}]");
+
+        let codes = job.value.get("codes").unwrap();
+        assert_eq!(codes.span, Span { start: 46, end: 58 });
+        let SpannedValue::List(items) = &codes.value else {
+            panic!("expected a list, got {codes:?}");
+        };
+        assert_eq!(span_of(&items[0]), "1");
+        assert_eq!(span_of(items[1].value.get("x").unwrap()), "2.5");
+
+        assert_eq!(
+            spanned.value.into_value(),
+            parse_document(input, DecoderOptions::default())?
+        );
+
+        let list = HuonValue::parse_spanned("  [1 2]  ", DecoderOptions::default())?;
+        assert_eq!(list.span, Span { start: 2, end: 7 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_block_scalar() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {"
+            job:
+                script: |
+                    set -e
+                      cargo build
+
+                    cargo test
+                name: \"ci\"
+            empty: |
+            after: 1
+        "};
+        let map = parse(input, DecoderOptions::default())?;
+
+        assert_eq!(
+            map,
+            map! {
+                "job" => HuonValue::Object(map! {
+                    "script" => HuonValue::String("set -e\n  cargo build\n\ncargo test\n".into()),
+                    "name" => HuonValue::String("ci".into())
+                }),
+                "empty" => HuonValue::String("".into()),
+                "after" => HuonValue::Int(1)
+            }
+        );
+
+        let err = parse("script: | echo", DecoderOptions::default()).unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::TokenizerError(TokenizerError::UnexpectedCharacter('e'))
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,154 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use super::value::HuonValue;
+use crate::{
+    DecoderOptions,
+    de::{HuonDeserializeError, HuonDeserializeErrorOwned},
+};
+
+/// An owned counterpart to [`HuonValue`] that doesn't borrow from the input.
+///
+/// Objects are stored in a `BTreeMap`, so their keys are always sorted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HuonValueOwned {
+    // String types
+    String(String),
+
+    // Numeric types
+    Int(i64),
+    BigInt(i128),
+    Float(f64),
+
+    // Bool types
+    Boolean(bool),
+
+    // Null types
+    Null,
+
+    List(Vec<HuonValueOwned>),
+
+    // Composite types
+    Object(BTreeMap<String, HuonValueOwned>),
+}
+
+impl HuonValueOwned {
+    /// Borrows this as a [`HuonValue`], e.g. to deserialize from it with
+    /// [`from_value`](crate::de::from_value).
+    #[must_use]
+    pub fn as_value(&self) -> HuonValue<'_> {
+        match self {
+            HuonValueOwned::String(s) => HuonValue::from(s.as_str()),
+            HuonValueOwned::Int(i) => HuonValue::Int(*i),
+            HuonValueOwned::BigInt(i) => HuonValue::BigInt(*i),
+            HuonValueOwned::Float(f) => HuonValue::Float(*f),
+            HuonValueOwned::Boolean(b) => HuonValue::Boolean(*b),
+            HuonValueOwned::Null => HuonValue::Null,
+            HuonValueOwned::List(list) => {
+                HuonValue::List(list.iter().map(HuonValueOwned::as_value).collect())
+            }
+            HuonValueOwned::Object(map) => HuonValue::Object(
+                map.iter()
+                    .map(|(key, value)| (key.as_str(), value.as_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Deep merges `other` into this value. Objects are merged key by key, anything else
+    /// (including lists) is replaced by the value from `other`.
+    pub fn merge(&mut self, other: HuonValueOwned) {
+        match (self, other) {
+            (HuonValueOwned::Object(map), HuonValueOwned::Object(other)) => {
+                for (key, value) in other {
+                    match map.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => {
+                            map.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+}
+
+impl From<&HuonValue<'_>> for HuonValueOwned {
+    fn from(value: &HuonValue<'_>) -> Self {
+        match value {
+            HuonValue::String(s) => HuonValueOwned::String(s.to_string()),
+            HuonValue::Int(i) => HuonValueOwned::Int(*i),
+            HuonValue::BigInt(i) => HuonValueOwned::BigInt(*i),
+            HuonValue::Float(f) => HuonValueOwned::Float(*f),
+            HuonValue::Boolean(b) => HuonValueOwned::Boolean(*b),
+            HuonValue::Null => HuonValueOwned::Null,
+            HuonValue::List(list) => HuonValueOwned::List(list.iter().map(Into::into).collect()),
+            HuonValue::Object(map) => HuonValueOwned::Object(
+                map.iter()
+                    .map(|(key, value)| ((*key).to_owned(), value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<HuonValue<'_>> for HuonValueOwned {
+    fn from(value: HuonValue<'_>) -> Self {
+        Self::from(&value)
+    }
+}
+
+/// Parses a document with the default [`DecoderOptions`], see [`HuonValue::parse`].
+impl FromStr for HuonValueOwned {
+    type Err = HuonDeserializeErrorOwned;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HuonValue::parse(s, DecoderOptions::default())
+            .map(Into::into)
+            .map_err(|e| HuonDeserializeError::ParserError(e).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_merge() -> Result<(), Box<dyn std::error::Error>> {
+        let parse = |input| -> Result<HuonValueOwned, Box<dyn std::error::Error>> {
+            Ok(HuonValue::parse(input, DecoderOptions::default())?.into())
+        };
+
+        let mut base = parse("a: 1\nb:\n    x: [1 2]\n    y: true\nc: \"c\"")?;
+        base.merge(parse("b:\n    x: [3]\n    z: null\nc:\n    d: 4")?);
+
+        assert_eq!(
+            base,
+            parse("a: 1\nb:\n    x: [3]\n    y: true\n    z: null\nc:\n    d: 4")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() -> Result<(), Box<dyn std::error::Error>> {
+        let value: HuonValueOwned = "name: \"huon\"\ntags: [1 2.5]".parse()?;
+
+        assert_eq!(
+            value,
+            HuonValueOwned::Object(BTreeMap::from([
+                ("name".to_owned(), HuonValueOwned::String("huon".to_owned())),
+                (
+                    "tags".to_owned(),
+                    HuonValueOwned::List(vec![HuonValueOwned::Int(1), HuonValueOwned::Float(2.5)])
+                ),
+            ]))
+        );
+
+        assert!("a: [1".parse::<HuonValueOwned>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use super::{
+    Span,
+    value::{HuonValue, PathSegment},
+};
+
+/// A value along with where it is in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+/// A [`HuonValue`] that knows where each of its parts is, see [`HuonValue::parse_spanned`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedValue<'a> {
+    /// Anything but a list or an object.
+    Scalar(HuonValue<'a>),
+    List(Vec<Spanned<SpannedValue<'a>>>),
+    Object(HashMap<&'a str, Spanned<SpannedValue<'a>>>),
+}
+
+impl<'a> SpannedValue<'a> {
+    /// Looks up `key` in this object, or returns `None` if this isn't one.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Spanned<SpannedValue<'a>>> {
+        match self {
+            SpannedValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Drops the spans, leaving the plain value.
+    #[must_use]
+    pub fn into_value(self) -> HuonValue<'a> {
+        match self {
+            SpannedValue::Scalar(value) => value,
+            SpannedValue::List(list) => HuonValue::List(
+                list.into_iter()
+                    .map(|item| item.value.into_value())
+                    .collect(),
+            ),
+            SpannedValue::Object(map) => HuonValue::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Pairs `value`, which is at `path`, and everything in it with the spans the parser recorded.
+pub(super) fn build<'a>(
+    value: HuonValue<'a>,
+    path: &mut Vec<PathSegment<'a>>,
+    spans: &HashMap<Vec<PathSegment<'a>>, Span>,
+) -> Spanned<SpannedValue<'a>> {
+    let span = spans.get(path).copied().unwrap_or_default();
+
+    let value = match value {
+        HuonValue::List(list) => SpannedValue::List(
+            list.into_iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    path.push(PathSegment::Index(i));
+                    let item = build(item, path, spans);
+                    path.pop();
+                    item
+                })
+                .collect(),
+        ),
+        HuonValue::Object(map) => SpannedValue::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    path.push(PathSegment::Key(key));
+                    let value = build(value, path, spans);
+                    path.pop();
+                    (key, value)
+                })
+                .collect(),
+        ),
+        scalar => SpannedValue::Scalar(scalar),
+    };
+
+    Spanned { value, span }
+}
@@ -1,13 +1,34 @@
-use std::{collections::HashMap, ops::Index};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    mem,
+    ops::Index,
+};
+
+use crate::DecoderOptions;
+
+use super::{
+    ParserError,
+    owned::HuonValueOwned,
+    spanned::{Spanned, SpannedValue},
+};
 
 /// Cloning is fairly cheap.
 #[derive(Debug, Clone, PartialEq)]
 pub enum HuonValue<'a> {
     // String types
-    String(&'a str),
+    /// Borrowed from the input, unless it had to be put together like a block scalar.
+    String(Cow<'a, str>),
 
     // Numeric types
     Int(i64),
+    /// An integer too large for an `i64`, the parser only uses this when `Int` doesn't fit.
+    BigInt(i128),
+    /// Any number written with a `.` or exponent, even an integral one like `2.0`, which is
+    /// also serialized back with its `.0`.
     Float(f64),
 
     // Bool types
@@ -22,6 +43,416 @@ pub enum HuonValue<'a> {
     Object(HashMap<&'a str, HuonValue<'a>>),
 }
 
+/// The variants of a [`HuonValue`] without their contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HuonKind {
+    String,
+    Int,
+    Float,
+    Boolean,
+    Null,
+    List,
+    Object,
+}
+
+impl<'a> HuonValue<'a> {
+    /// Parses a whole document, usually into its top-level object.
+    /// A document holding nothing but a single list or scalar is read as that value.
+    pub fn parse(input: &'a str, options: DecoderOptions) -> Result<Self, ParserError<'a>> {
+        super::parse_document(input, options)
+    }
+
+    /// Parses a whole document like [`HuonValue::parse`], also keeping where each value is in
+    /// the input, e.g. to point an editor at a value. See [`Parser::parse_spanned`](super::Parser::parse_spanned).
+    pub fn parse_spanned(
+        input: &'a str,
+        options: DecoderOptions,
+    ) -> Result<Spanned<SpannedValue<'a>>, ParserError<'a>> {
+        super::parse_spanned(input, options)
+    }
+
+    /// Looks up `key` in this object. Unlike indexing, this returns an error instead of
+    /// panicking, which makes it handy for validation code using `?`.
+    pub fn get_value(&self, key: &str) -> Result<&HuonValue<'a>, HuonAccessError> {
+        match self {
+            HuonValue::Object(map) => map
+                .get(key)
+                .ok_or_else(|| HuonAccessError::MissingKey(key.to_string())),
+            value => Err(HuonAccessError::NotAnObject {
+                found: value.type_name(),
+            }),
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<&str, HuonAccessError> {
+        self.get_as(key, "string", |value| match value {
+            HuonValue::String(s) => Some(s.as_ref()),
+            _ => None,
+        })
+    }
+
+    pub fn get_int(&self, key: &str) -> Result<i64, HuonAccessError> {
+        self.get_as(key, "int", |value| match value {
+            HuonValue::Int(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    pub fn get_float(&self, key: &str) -> Result<f64, HuonAccessError> {
+        self.get_as(key, "float", |value| match value {
+            HuonValue::Float(f) => Some(*f),
+            _ => None,
+        })
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, HuonAccessError> {
+        self.get_as(key, "bool", |value| match value {
+            HuonValue::Boolean(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    pub fn get_list(&self, key: &str) -> Result<&[HuonValue<'a>], HuonAccessError> {
+        self.get_as(key, "list", |value| match value {
+            HuonValue::List(list) => Some(list.as_slice()),
+            _ => None,
+        })
+    }
+
+    pub fn get_object(
+        &self,
+        key: &str,
+    ) -> Result<&HashMap<&'a str, HuonValue<'a>>, HuonAccessError> {
+        self.get_as(key, "object", |value| match value {
+            HuonValue::Object(map) => Some(map),
+            _ => None,
+        })
+    }
+
+    fn get_as<'s, T>(
+        &'s self,
+        key: &str,
+        expected: &'static str,
+        convert: impl FnOnce(&'s HuonValue<'a>) -> Option<T>,
+    ) -> Result<T, HuonAccessError> {
+        let value = self.get_value(key)?;
+
+        convert(value).ok_or_else(|| HuonAccessError::WrongType {
+            key: key.to_string(),
+            expected,
+            found: value.type_name(),
+        })
+    }
+
+    /// The floats in this list, or `None` if it isn't one or holds anything but floats.
+    /// Ints count as the wrong type too, like they do for [`HuonValue::get_float`].
+    #[must_use]
+    pub fn as_f64_vec(&self) -> Option<Vec<f64>> {
+        self.as_vec_of(|value| match value {
+            HuonValue::Float(f) => Some(*f),
+            _ => None,
+        })
+    }
+
+    /// The ints in this list, or `None` if it isn't one or holds anything but ints.
+    #[must_use]
+    pub fn as_i64_vec(&self) -> Option<Vec<i64>> {
+        self.as_vec_of(|value| match value {
+            HuonValue::Int(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    /// The strings in this list, or `None` if it isn't one or holds anything but strings.
+    #[must_use]
+    pub fn as_str_vec(&self) -> Option<Vec<&str>> {
+        self.as_vec_of(|value| match value {
+            HuonValue::String(s) => Some(s.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// The booleans in this list, or `None` if it isn't one or holds anything but booleans.
+    #[must_use]
+    pub fn as_bool_vec(&self) -> Option<Vec<bool>> {
+        self.as_vec_of(|value| match value {
+            HuonValue::Boolean(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    fn as_vec_of<'s, T>(
+        &'s self,
+        convert: impl FnMut(&'s HuonValue<'a>) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        match self {
+            HuonValue::List(list) => list.iter().map(convert).collect(),
+            _ => None,
+        }
+    }
+
+    /// Calls `visitor` for this value and everything in it, parents before their children,
+    /// along with the path to each one. The path of this value itself is empty.
+    /// Lists are walked in order, objects in no particular one.
+    pub fn walk(&self, visitor: &mut impl FnMut(&[PathSegment<'a>], &HuonValue<'a>)) {
+        self.walk_at(&mut Vec::new(), visitor);
+    }
+
+    /// Collects the values that aren't lists or objects, keyed by their path with the steps
+    /// joined by dots, like `job1.info.pay` or `codes.1` for the second entry of `codes`.
+    /// Empty lists and objects are kept as they are, so nothing goes missing.
+    #[must_use]
+    pub fn flatten_keys(&self) -> BTreeMap<String, HuonValue<'a>> {
+        let mut flat = BTreeMap::new();
+
+        self.walk(&mut |path, value| {
+            let is_leaf = match value {
+                HuonValue::List(list) => list.is_empty(),
+                HuonValue::Object(map) => map.is_empty(),
+                _ => true,
+            };
+
+            if is_leaf {
+                let path: Vec<_> = path
+                    .iter()
+                    .map(|segment| match segment {
+                        PathSegment::Key(key) => (*key).to_string(),
+                        PathSegment::Index(idx) => idx.to_string(),
+                    })
+                    .collect();
+                flat.insert(path.join("."), value.clone());
+            }
+        });
+
+        flat
+    }
+
+    fn walk_at(
+        &self,
+        path: &mut Vec<PathSegment<'a>>,
+        visitor: &mut impl FnMut(&[PathSegment<'a>], &HuonValue<'a>),
+    ) {
+        visitor(path, self);
+
+        match self {
+            HuonValue::List(list) => {
+                for (idx, value) in list.iter().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    value.walk_at(path, visitor);
+                    path.pop();
+                }
+            }
+            HuonValue::Object(map) => {
+                for (key, value) in map {
+                    path.push(PathSegment::Key(key));
+                    value.walk_at(path, visitor);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// One step on the way to a nested value, see [`HuonValue::walk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Writes keys as they are and indices in brackets, like `codes[1]`, so a path can be
+/// joined the way deserialize errors write them.
+impl fmt::Display for PathSegment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Key(key) => f.write_str(key),
+            PathSegment::Index(idx) => write!(f, "[{idx}]"),
+        }
+    }
+}
+
+impl HuonValue<'_> {
+    /// A short, human readable name of the variant, used in error messages.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            HuonValue::String(_) => "string",
+            HuonValue::Int(_) | HuonValue::BigInt(_) => "int",
+            HuonValue::Float(_) => "float",
+            HuonValue::Boolean(_) => "bool",
+            HuonValue::Null => "null",
+            HuonValue::List(_) => "list",
+            HuonValue::Object(_) => "object",
+        }
+    }
+
+    #[must_use]
+    pub fn is_object(&self) -> bool {
+        matches!(self, HuonValue::Object(_))
+    }
+
+    #[must_use]
+    pub fn is_list(&self) -> bool {
+        matches!(self, HuonValue::List(_))
+    }
+
+    /// Whether this is a single value, so anything but a list or an object. `null` counts too.
+    #[must_use]
+    pub fn is_scalar(&self) -> bool {
+        !self.is_object() && !self.is_list()
+    }
+
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, HuonValue::Null)
+    }
+
+    /// Compares like `==`, except that numbers are equal when they have the same value,
+    /// whatever variant they are. `Int(5)` is loosely equal to `Float(5.0)`, but not to
+    /// `Float(5.5)` or `String("5")`. Lists and objects are compared like this all the way down.
+    #[must_use]
+    pub fn loosely_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (HuonValue::Int(i), HuonValue::Float(f)) | (HuonValue::Float(f), HuonValue::Int(i)) => {
+                int_eq_float(i128::from(*i), *f)
+            }
+            (HuonValue::BigInt(i), HuonValue::Float(f))
+            | (HuonValue::Float(f), HuonValue::BigInt(i)) => int_eq_float(*i, *f),
+            (HuonValue::Int(a), HuonValue::BigInt(b))
+            | (HuonValue::BigInt(b), HuonValue::Int(a)) => i128::from(*a) == *b,
+            (HuonValue::List(a), HuonValue::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.loosely_eq(b))
+            }
+            (HuonValue::Object(a), HuonValue::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.loosely_eq(b)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> HuonKind {
+        match self {
+            HuonValue::String(_) => HuonKind::String,
+            HuonValue::Int(_) | HuonValue::BigInt(_) => HuonKind::Int,
+            HuonValue::Float(_) => HuonKind::Float,
+            HuonValue::Boolean(_) => HuonKind::Boolean,
+            HuonValue::Null => HuonKind::Null,
+            HuonValue::List(_) => HuonKind::List,
+            HuonValue::Object(_) => HuonKind::Object,
+        }
+    }
+
+    /// Converts this value into `kind`, as long as that doesn't lose anything. Strings are
+    /// parsed (`"42"` to `42`, `"true"` to `true`), ints become floats, floats without a
+    /// fractional part become ints, and scalars become strings. Returns `None` otherwise.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn coerce_to(&self, kind: HuonKind) -> Option<HuonValueOwned> {
+        if self.kind() == kind {
+            return Some(self.sorted());
+        }
+
+        Some(match (self, kind) {
+            (HuonValue::String(s), HuonKind::Int) => HuonValueOwned::Int(s.trim().parse().ok()?),
+            (HuonValue::String(s), HuonKind::Float) => {
+                HuonValueOwned::Float(s.trim().parse().ok()?)
+            }
+            (HuonValue::String(s), HuonKind::Boolean) => {
+                HuonValueOwned::Boolean(s.trim().parse().ok()?)
+            }
+            (HuonValue::String(s), HuonKind::Null) if s.trim() == "null" => HuonValueOwned::Null,
+
+            // Every integer up to 2^53 is exactly representable as an f64
+            (HuonValue::Int(i), HuonKind::Float) if i.unsigned_abs() <= 1 << 53 => {
+                HuonValueOwned::Float(*i as f64)
+            }
+            (HuonValue::Float(f), HuonKind::Int)
+                if f.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(f) =>
+            {
+                HuonValueOwned::Int(*f as i64)
+            }
+
+            (HuonValue::Int(i), HuonKind::String) => HuonValueOwned::String(i.to_string()),
+            (HuonValue::BigInt(i), HuonKind::String) => HuonValueOwned::String(i.to_string()),
+            (HuonValue::Float(f), HuonKind::String) => HuonValueOwned::String(f.to_string()),
+            (HuonValue::Boolean(b), HuonKind::String) => HuonValueOwned::String(b.to_string()),
+
+            _ => return None,
+        })
+    }
+
+    /// Returns a deep copy with every object's keys in sorted order, lists keep their order.
+    /// Useful to compare documents regardless of how their keys were authored.
+    #[must_use]
+    pub fn sorted(&self) -> HuonValueOwned {
+        HuonValueOwned::from(self)
+    }
+
+    /// Sorts a list of strings, numbers or booleans in place and returns `true`.
+    /// Lists mixing kinds, holding lists, objects or `NaN`, and values that aren't lists,
+    /// are left as they are and `false` is returned.
+    pub fn sort_list(&mut self) -> bool {
+        let HuonValue::List(list) = self else {
+            return false;
+        };
+
+        let Some(kind) = list.first().map(HuonValue::kind) else {
+            return true;
+        };
+
+        let sortable = matches!(
+            kind,
+            HuonKind::String | HuonKind::Int | HuonKind::Float | HuonKind::Boolean
+        ) && list.iter().all(|value| {
+            value.kind() == kind && !matches!(value, HuonValue::Float(f) if f.is_nan())
+        });
+
+        if sortable {
+            // every pair of same kind scalars without NaN has an order
+            list.sort_by(|a, b| match (as_i128(a), as_i128(b)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            });
+        }
+        sortable
+    }
+
+    /// Removes every entry of an object for which `f` returns `false`.
+    /// Does nothing if this isn't an object.
+    pub fn retain(&mut self, mut f: impl FnMut(&str, &HuonValue<'_>) -> bool) {
+        if let HuonValue::Object(map) = self {
+            map.retain(|key, value| f(key, value));
+        }
+    }
+
+    /// Like [`HuonValue::retain`], but also filters every nested object, including the ones in
+    /// lists. Entries are checked before their contents, so a removed entry isn't descended into.
+    pub fn retain_recursive(&mut self, mut f: impl FnMut(&str, &HuonValue<'_>) -> bool) {
+        self.retain_recursive_dyn(&mut f);
+    }
+
+    fn retain_recursive_dyn(&mut self, f: &mut dyn FnMut(&str, &HuonValue<'_>) -> bool) {
+        match self {
+            HuonValue::Object(map) => {
+                map.retain(|key, value| f(key, value));
+                for value in map.values_mut() {
+                    value.retain_recursive_dyn(f);
+                }
+            }
+            HuonValue::List(list) => {
+                for value in list {
+                    value.retain_recursive_dyn(f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl<'a> Index<&'_ str> for HuonValue<'a> {
     type Output = HuonValue<'a>;
 
@@ -32,3 +463,721 @@ impl<'a> Index<&'_ str> for HuonValue<'a> {
         }
     }
 }
+
+/// Floats are hashed by their bit pattern, with `-0.0` hashed like `0.0` (as they're equal) and
+/// every NaN hashed alike. Note that NaN still never equals itself, so a value containing one can't
+/// be found again after inserting it into a set or using it as a map key.
+impl Hash for HuonValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            HuonValue::String(s) => s.hash(state),
+            HuonValue::Int(i) => i.hash(state),
+            HuonValue::BigInt(i) => i.hash(state),
+            HuonValue::Float(f) => canonical_bits(*f).hash(state),
+            HuonValue::Boolean(b) => b.hash(state),
+            HuonValue::Null => {}
+            HuonValue::List(list) => list.hash(state),
+            HuonValue::Object(map) => {
+                // a `HashMap` has no stable order, so the entries are combined order-independently
+                let entries = map
+                    .iter()
+                    .map(|entry| {
+                        let mut hasher = DefaultHasher::new();
+                        entry.hash(&mut hasher);
+                        hasher.finish()
+                    })
+                    .fold(0, u64::wrapping_add);
+
+                map.len().hash(state);
+                entries.hash(state);
+            }
+        }
+    }
+}
+
+/// See the [`Hash`] impl for the caveat about NaN.
+impl Eq for HuonValue<'_> {}
+
+/// Only scalars of the same type are ordered, e.g. `Int(1) < Int(2)` or `"a" < "b"`.
+/// An `Int` isn't comparable to a `BigInt` or a `Float`, and neither are lists or objects,
+/// unless they're equal.
+impl PartialOrd for HuonValue<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (HuonValue::String(a), HuonValue::String(b)) => a.partial_cmp(b),
+            (HuonValue::Int(a), HuonValue::Int(b)) => a.partial_cmp(b),
+            (HuonValue::BigInt(a), HuonValue::BigInt(b)) => a.partial_cmp(b),
+            (HuonValue::Float(a), HuonValue::Float(b)) => a.partial_cmp(b),
+            (HuonValue::Boolean(a), HuonValue::Boolean(b)) => a.partial_cmp(b),
+            (a, b) => (a == b).then_some(Ordering::Equal),
+        }
+    }
+}
+
+fn as_i128(value: &HuonValue<'_>) -> Option<i128> {
+    match value {
+        HuonValue::Int(i) => Some(i128::from(*i)),
+        HuonValue::BigInt(i) => Some(*i),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn int_eq_float(i: i128, f: f64) -> bool {
+    f.fract() == 0.0 && (i128::MIN as f64..i128::MAX as f64).contains(&f) && f as i128 == i
+}
+
+fn canonical_bits(f: f64) -> u64 {
+    if f.is_nan() {
+        f64::NAN.to_bits()
+    } else if f == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        f.to_bits()
+    }
+}
+
+/// Returned when converting a [`HuonValue`] into a Rust type that doesn't match its variant.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq, Eq)]
+#[error("Expected {expected}, found {found}")]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+/// Returned by the `get_*` accessors of [`HuonValue`].
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum HuonAccessError {
+    #[error("Expected an object, found {found}")]
+    NotAnObject { found: &'static str },
+
+    #[error("Missing key `{_0}`")]
+    MissingKey(String),
+
+    #[error("Expected `{key}` to be {expected}, found {found}")]
+    WrongType {
+        key: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+macro_rules! impl_try_from {
+    ($ty:ty, $expected:literal, $variant:ident($inner:ident) => $convert:expr) => {
+        impl TryFrom<HuonValue<'_>> for $ty {
+            type Error = ConversionError;
+
+            fn try_from(value: HuonValue<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    HuonValue::$variant($inner) => Ok($convert),
+                    value => Err(ConversionError {
+                        expected: $expected,
+                        found: value.type_name(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from!(i64, "int", Int(i) => i);
+impl_try_from!(f64, "float", Float(f) => f);
+impl_try_from!(bool, "bool", Boolean(b) => b);
+impl_try_from!(String, "string", String(s) => s.into_owned());
+
+impl From<i64> for HuonValue<'_> {
+    fn from(value: i64) -> Self {
+        HuonValue::Int(value)
+    }
+}
+
+impl From<f64> for HuonValue<'_> {
+    fn from(value: f64) -> Self {
+        HuonValue::Float(value)
+    }
+}
+
+impl From<bool> for HuonValue<'_> {
+    fn from(value: bool) -> Self {
+        HuonValue::Boolean(value)
+    }
+}
+
+impl<'a> From<&'a str> for HuonValue<'a> {
+    fn from(value: &'a str) -> Self {
+        HuonValue::String(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<Vec<HuonValue<'a>>> for HuonValue<'a> {
+    fn from(value: Vec<HuonValue<'a>>) -> Self {
+        HuonValue::List(value)
+    }
+}
+
+/// Compares with a plain value, which is only equal to the variant holding that type.
+/// So `HuonValue::Int(1) == 1.0` is `false`, see [`HuonValue::loosely_eq`] for that.
+macro_rules! impl_partial_eq {
+    ($ty:ty, $variant:ident) => {
+        impl PartialEq<$ty> for HuonValue<'_> {
+            fn eq(&self, other: &$ty) -> bool {
+                matches!(self, HuonValue::$variant(value) if value == other)
+            }
+        }
+    };
+}
+
+impl_partial_eq!(i64, Int);
+impl_partial_eq!(f64, Float);
+impl_partial_eq!(bool, Boolean);
+
+impl PartialEq<str> for HuonValue<'_> {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, HuonValue::String(value) if value == other)
+    }
+}
+
+impl PartialEq<&str> for HuonValue<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+
+    use crate::parser::parse;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_try_from_matching_variant() -> Result<(), ConversionError> {
+        assert_eq!(i64::try_from(HuonValue::Int(32))?, 32);
+        assert_eq!(f64::try_from(HuonValue::Float(-4200.5))?, -4200.5);
+        assert!(bool::try_from(HuonValue::Boolean(true))?);
+        assert_eq!(String::try_from(HuonValue::String("John".into()))?, "John");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from_mismatched_variant() {
+        let err = i64::try_from(HuonValue::String("32".into())).unwrap_err();
+
+        assert_eq!(
+            err,
+            ConversionError {
+                expected: "int",
+                found: "string"
+            }
+        );
+        assert_eq!(err.to_string(), "Expected int, found string");
+
+        assert!(f64::try_from(HuonValue::Int(3700)).is_err());
+        assert!(bool::try_from(HuonValue::Null).is_err());
+        assert!(String::try_from(HuonValue::List(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_from_scalars() {
+        let int: HuonValue = 5.into();
+        let float: HuonValue = 3.5.into();
+        let boolean: HuonValue = true.into();
+        let string: HuonValue = "IT".into();
+
+        assert_eq!(int, HuonValue::Int(5));
+        assert_eq!(float, HuonValue::Float(3.5));
+        assert_eq!(boolean, HuonValue::Boolean(true));
+        assert_eq!(string, HuonValue::String("IT".into()));
+    }
+
+    #[test]
+    fn test_from_list() {
+        let list: HuonValue = vec![1.into(), "a".into(), false.into()].into();
+
+        assert_eq!(
+            list,
+            HuonValue::List(vec![
+                HuonValue::Int(1),
+                HuonValue::String("a".into()),
+                HuonValue::Boolean(false),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sorted_ignores_authoring_order() -> Result<(), Box<dyn std::error::Error>> {
+        let a = HuonValue::Object(parse(
+            "b: 2\na: [3 1]\nc:\n    y: 1\n    x: 2",
+            DecoderOptions::default(),
+        )?);
+        let b = HuonValue::Object(parse(
+            "c:\n    x: 2\n    y: 1\na: [3 1]\nb: 2",
+            DecoderOptions::default(),
+        )?);
+
+        let sorted = a.sorted();
+        assert_eq!(sorted, b.sorted());
+
+        let HuonValueOwned::Object(map) = sorted else {
+            panic!("Not an object");
+        };
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(
+            map["a"],
+            HuonValueOwned::List(vec![HuonValueOwned::Int(3), HuonValueOwned::Int(1)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_parse_document() -> Result<(), Box<dyn std::error::Error>> {
+        let value = HuonValue::parse(include_str!("../../test.huon"), DecoderOptions::default())?;
+
+        assert_eq!(value["name"], HuonValue::String("John".into()));
+        assert_eq!(value["job1"]["info"]["pay"], HuonValue::Float(-4200.5));
+        assert_eq!(
+            value["job2"]["info"]["payrate"]["iteration"],
+            HuonValue::String("weekly".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_set() -> Result<(), Box<dyn std::error::Error>> {
+        let mut set = HashSet::new();
+
+        assert!(set.insert(HuonValue::Int(1)));
+        assert!(set.insert(HuonValue::Float(1.0)));
+        assert!(set.insert(HuonValue::String("1".into())));
+        assert!(set.insert(HuonValue::Null));
+        assert!(set.insert(HuonValue::List(vec![1.into(), 2.into()])));
+        assert!(set.insert(HuonValue::parse(
+            "a: 1\nb: [2.5]",
+            DecoderOptions::default()
+        )?));
+
+        assert!(!set.insert(HuonValue::Int(1)));
+        assert!(!set.insert(HuonValue::Float(1.0)));
+        assert!(!set.insert(HuonValue::List(vec![1.into(), 2.into()])));
+        assert!(!set.insert(HuonValue::parse(
+            "b: [2.5]\na: 1",
+            DecoderOptions::default()
+        )?));
+
+        assert!(set.insert(HuonValue::Float(0.0)));
+        assert!(!set.insert(HuonValue::Float(-0.0)));
+
+        assert_eq!(set.len(), 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retain() -> Result<(), Box<dyn std::error::Error>> {
+        let input = indoc! {r#"
+            user: "john"
+            password: "hunter2"
+            db:
+                db_password: "secret"
+                hosts: [{ host: "a", password: "b" }]"#};
+
+        let not_password = |key: &str, _: &HuonValue<'_>| !key.contains("password");
+
+        let mut value = HuonValue::parse(input, DecoderOptions::default())?;
+        value.retain(not_password);
+
+        let expected = HuonValue::parse(
+            indoc! {r#"
+                user: "john"
+                db:
+                    db_password: "secret"
+                    hosts: [{ host: "a", password: "b" }]"#},
+            DecoderOptions::default(),
+        )?;
+        assert_eq!(value, expected);
+
+        let mut value = HuonValue::parse(input, DecoderOptions::default())?;
+        value.retain_recursive(not_password);
+
+        let expected = HuonValue::parse(
+            indoc! {r#"
+                user: "john"
+                db:
+                    hosts: [{ host: "a" }]"#},
+            DecoderOptions::default(),
+        )?;
+        assert_eq!(value, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_ord() {
+        assert!(HuonValue::Int(1) < HuonValue::Int(2));
+        assert!(HuonValue::String("a".into()) < HuonValue::String("b".into()));
+        assert!(HuonValue::Float(-0.5) <= HuonValue::Float(0.0));
+        assert!(HuonValue::Boolean(false) < HuonValue::Boolean(true));
+
+        assert_eq!(HuonValue::Int(1).partial_cmp(&HuonValue::Float(2.0)), None);
+        assert_eq!(HuonValue::Int(5).partial_cmp(&HuonValue::BigInt(5)), None);
+        assert_eq!(HuonValue::Null.partial_cmp(&HuonValue::Int(0)), None);
+        assert_eq!(
+            HuonValue::List(vec![1.into()]).partial_cmp(&HuonValue::List(vec![2.into()])),
+            None
+        );
+        assert_eq!(
+            HuonValue::Null.partial_cmp(&HuonValue::Null),
+            Some(Ordering::Equal)
+        );
+
+        let sorted = |list: &[HuonValue]| list.windows(2).all(|w| w[0] <= w[1]);
+
+        assert!(sorted(&[1.into(), 2.into(), 2.into(), 5.into()]));
+        assert!(!sorted(&["b".into(), "a".into()]));
+        assert!(!sorted(&[1.into(), "a".into()]));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_coerce_to() {
+        assert_eq!(
+            HuonValue::String("42".into()).coerce_to(HuonKind::Int),
+            Some(HuonValueOwned::Int(42))
+        );
+        assert_eq!(
+            HuonValue::String("true".into()).coerce_to(HuonKind::Boolean),
+            Some(HuonValueOwned::Boolean(true))
+        );
+        assert_eq!(
+            HuonValue::String("-2.5".into()).coerce_to(HuonKind::Float),
+            Some(HuonValueOwned::Float(-2.5))
+        );
+        assert_eq!(
+            HuonValue::Int(1).coerce_to(HuonKind::Float),
+            Some(HuonValueOwned::Float(1.0))
+        );
+        assert_eq!(
+            HuonValue::Float(3.0).coerce_to(HuonKind::Int),
+            Some(HuonValueOwned::Int(3))
+        );
+        assert_eq!(
+            HuonValue::Boolean(false).coerce_to(HuonKind::String),
+            Some(HuonValueOwned::String("false".to_string()))
+        );
+        assert_eq!(
+            HuonValue::List(vec![1.into()]).coerce_to(HuonKind::List),
+            Some(HuonValueOwned::List(vec![HuonValueOwned::Int(1)]))
+        );
+
+        assert_eq!(
+            HuonValue::String("4x".into()).coerce_to(HuonKind::Int),
+            None
+        );
+        assert_eq!(
+            HuonValue::String("yes".into()).coerce_to(HuonKind::Boolean),
+            None
+        );
+        assert_eq!(HuonValue::Float(3.5).coerce_to(HuonKind::Int), None);
+        assert_eq!(HuonValue::Int(i64::MAX).coerce_to(HuonKind::Float), None);
+        assert_eq!(HuonValue::Null.coerce_to(HuonKind::Object), None);
+    }
+
+    #[test]
+    fn test_get_accessors() -> Result<(), Box<dyn std::error::Error>> {
+        let value = HuonValue::parse(
+            indoc! {r#"
+                name: "John"
+                age: 32
+                pay: 4200.5
+                employed: true
+                codes: [1 2]
+                job:
+                    title: "Engineer"
+            "#},
+            DecoderOptions::default(),
+        )?;
+
+        assert_eq!(value.get_str("name")?, "John");
+        assert_eq!(value.get_int("age")?, 32);
+        assert!(value.get_bool("employed")?);
+        assert_eq!(value.get_list("codes")?.len(), 2);
+        assert_eq!(value.get_object("job")?.len(), 1);
+        assert_eq!(value.get_value("job")?.get_str("title")?, "Engineer");
+
+        assert_eq!(
+            value.get_str("last_name"),
+            Err(HuonAccessError::MissingKey("last_name".to_string()))
+        );
+
+        let err = value.get_str("age").unwrap_err();
+        assert_eq!(
+            err,
+            HuonAccessError::WrongType {
+                key: "age".to_string(),
+                expected: "string",
+                found: "int",
+            }
+        );
+        assert_eq!(err.to_string(), "Expected `age` to be string, found int");
+
+        assert_eq!(
+            value.get_value("age")?.get_int("x"),
+            Err(HuonAccessError::NotAnObject { found: "int" })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_predicates() {
+        let values = [
+            HuonValue::String("a".into()),
+            HuonValue::Int(1),
+            HuonValue::Float(1.5),
+            HuonValue::Boolean(true),
+            HuonValue::Null,
+            HuonValue::List(vec![]),
+            HuonValue::Object(HashMap::new()),
+        ];
+
+        let predicates: Vec<_> = values
+            .iter()
+            .map(|value| {
+                (
+                    value.is_object(),
+                    value.is_list(),
+                    value.is_scalar(),
+                    value.is_null(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            predicates,
+            vec![
+                (false, false, true, false),
+                (false, false, true, false),
+                (false, false, true, false),
+                (false, false, true, false),
+                (false, false, true, true),
+                (false, true, false, false),
+                (true, false, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loosely_eq() {
+        assert!(HuonValue::Int(5).loosely_eq(&HuonValue::Float(5.0)));
+        assert!(HuonValue::Float(5.0).loosely_eq(&HuonValue::Int(5)));
+        assert!(HuonValue::BigInt(5).loosely_eq(&HuonValue::Int(5)));
+        assert!(!HuonValue::Int(5).loosely_eq(&HuonValue::Float(5.5)));
+        assert!(!HuonValue::Int(5).loosely_eq(&HuonValue::String("5".into())));
+        assert_ne!(HuonValue::Int(5), HuonValue::Float(5.0));
+
+        assert!(HuonValue::String("a".into()).loosely_eq(&HuonValue::String("a".into())));
+        assert!(!HuonValue::String("a".into()).loosely_eq(&HuonValue::String("A".into())));
+        assert!(!HuonValue::String("5".into()).loosely_eq(&HuonValue::String("5.0".into())));
+
+        let a = HuonValue::Object(HashMap::from([(
+            "pay",
+            HuonValue::List(vec![HuonValue::Int(1), HuonValue::Float(2.5)]),
+        )]));
+        let b = HuonValue::Object(HashMap::from([(
+            "pay",
+            HuonValue::List(vec![HuonValue::Float(1.0), HuonValue::Float(2.5)]),
+        )]));
+        assert!(a.loosely_eq(&b));
+        assert!(!a.loosely_eq(&HuonValue::Object(HashMap::new())));
+    }
+
+    #[test]
+    fn test_walk_leaf_paths() {
+        let value =
+            HuonValue::parse(include_str!("../../test.huon"), DecoderOptions::default()).unwrap();
+
+        let mut leaves = Vec::new();
+        let mut nodes = 0;
+        value.walk(&mut |path, value| {
+            nodes += 1;
+            if value.is_scalar() {
+                let path: Vec<_> = path.iter().map(ToString::to_string).collect();
+                leaves.push(path.join("."));
+            }
+        });
+        leaves.sort();
+
+        assert_eq!(
+            leaves,
+            vec![
+                "age",
+                "job1.category.name",
+                "job1.info.pay",
+                "job1.info.payrate.date",
+                "job1.info.payrate.iteration",
+                "job1.info.payrate.monthly_increase",
+                "job1.name",
+                "job2.category.name",
+                "job2.info.pay",
+                "job2.info.payrate.date",
+                "job2.info.payrate.iteration",
+                "job2.name",
+                "last_name",
+                "name",
+            ]
+        );
+        // the root, `job1`, `job2` and their three objects each
+        assert_eq!(nodes, leaves.len() + 9);
+
+        let list = HuonValue::List(vec![
+            HuonValue::Int(1),
+            HuonValue::List(vec![HuonValue::Null]),
+        ]);
+        let mut paths = Vec::new();
+        list.walk(&mut |path, _| paths.push(path.to_vec()));
+        assert_eq!(
+            paths,
+            vec![
+                vec![],
+                vec![PathSegment::Index(0)],
+                vec![PathSegment::Index(1)],
+                vec![PathSegment::Index(1), PathSegment::Index(0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flatten_keys() {
+        let value =
+            HuonValue::parse(include_str!("../../test.huon"), DecoderOptions::default()).unwrap();
+
+        let flat = value.flatten_keys();
+
+        assert_eq!(flat.len(), 14);
+        assert_eq!(flat["job1.info.pay"], HuonValue::Float(-4200.5));
+        assert_eq!(
+            flat["job2.info.payrate.iteration"],
+            HuonValue::String("weekly".into())
+        );
+        assert!(!flat.contains_key("job1.info"));
+
+        let value = parse("codes: [1 [2]]\nempty: []", DecoderOptions::default())
+            .map(HuonValue::Object)
+            .unwrap();
+        let keys: Vec<_> = value.flatten_keys().into_keys().collect();
+        assert_eq!(keys, vec!["codes.0", "codes.1.0", "empty"]);
+    }
+
+    #[test]
+    fn test_sort_list() {
+        let mut ints = HuonValue::List(vec![
+            HuonValue::Int(3),
+            HuonValue::BigInt(i128::from(i64::MAX) + 1),
+            HuonValue::Int(-1),
+            HuonValue::Int(2),
+        ]);
+        assert!(ints.sort_list());
+        assert_eq!(
+            ints,
+            HuonValue::List(vec![
+                HuonValue::Int(-1),
+                HuonValue::Int(2),
+                HuonValue::Int(3),
+                HuonValue::BigInt(i128::from(i64::MAX) + 1),
+            ])
+        );
+
+        let mut strings = HuonValue::List(vec![
+            HuonValue::String("pear".into()),
+            HuonValue::String("apple".into()),
+            HuonValue::String("fig".into()),
+        ]);
+        assert!(strings.sort_list());
+        assert_eq!(
+            strings,
+            HuonValue::List(vec![
+                HuonValue::String("apple".into()),
+                HuonValue::String("fig".into()),
+                HuonValue::String("pear".into()),
+            ])
+        );
+
+        let mixed = HuonValue::List(vec![
+            HuonValue::Int(2),
+            HuonValue::String("a".into()),
+            HuonValue::Float(1.5),
+        ]);
+        let mut sorted = mixed.clone();
+        assert!(!sorted.sort_list());
+        assert_eq!(sorted, mixed);
+
+        let mut nan = HuonValue::List(vec![HuonValue::Float(f64::NAN), HuonValue::Float(1.0)]);
+        assert!(!nan.sort_list());
+        assert!(!HuonValue::Int(1).sort_list());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_partial_eq_scalars() -> Result<(), Box<dyn std::error::Error>> {
+        let value = HuonValue::parse(
+            "name: \"John\"\nage: 32\nheight: 1.8\nemployed: true",
+            DecoderOptions::default(),
+        )?;
+
+        assert!(value["name"] == "John");
+        assert!(value["name"] == *"John");
+        assert!(value["age"] == 32);
+        assert!(value["height"] == 1.8);
+        assert!(value["employed"] == true);
+
+        assert!(value["name"] != "Jane");
+        assert!(value["age"] != 33);
+
+        // only the matching variant compares equal
+        assert!(value["age"] != 32.0);
+        assert!(value["height"] != "1.8");
+        assert!(value["name"] != true);
+        assert!(HuonValue::Null != 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_typed_vec() -> Result<(), Box<dyn std::error::Error>> {
+        let value = HuonValue::parse(
+            indoc! {r#"
+                weights: [0.5 1.25 2.0]
+                codes: [1 2 3]
+                tags: ["a" "b"]
+                flags: [true false]
+                mixed: [1 2.5 "three"]
+                empty: []
+            "#},
+            DecoderOptions::default(),
+        )?;
+
+        assert_eq!(value["weights"].as_f64_vec(), Some(vec![0.5, 1.25, 2.0]));
+        assert_eq!(value["codes"].as_i64_vec(), Some(vec![1, 2, 3]));
+        assert_eq!(value["tags"].as_str_vec(), Some(vec!["a", "b"]));
+        assert_eq!(value["flags"].as_bool_vec(), Some(vec![true, false]));
+        assert_eq!(value["empty"].as_i64_vec(), Some(vec![]));
+
+        assert_eq!(value["mixed"].as_f64_vec(), None);
+        assert_eq!(value["mixed"].as_i64_vec(), None);
+        assert_eq!(value["codes"].as_f64_vec(), None);
+        assert_eq!(value["tags"].as_bool_vec(), None);
+        assert_eq!(value.as_str_vec(), None);
+
+        Ok(())
+    }
+}
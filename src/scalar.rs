@@ -0,0 +1,228 @@
+//! Custom scalars, values with their own syntax like a color written as `#FF00FF`.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// The newtype name the serializer recognizes to write a scalar's text without quotes.
+pub(crate) const RAW_SCALAR: &str = "$huon::private::RawScalar";
+
+/// A value written as a word that starts with [`HuonScalar::PREFIX`], like `#FF00FF`.
+///
+/// Fields of such a type use `#[serde(with = "huon::scalar")]`, and the type is registered
+/// for reading with [`DecoderOptions::with_scalar`](crate::DecoderOptions::with_scalar).
+/// Only one scalar can be registered at a time. Other formats see the text as a string.
+pub trait HuonScalar: Sized {
+    /// The character the scalar's text starts with.
+    const PREFIX: char;
+
+    /// Reads the scalar from its text, which includes the prefix.
+    fn from_raw(raw: &str) -> Result<Self, String>;
+
+    /// Writes the scalar's text, including the prefix. It may only contain letters, digits and
+    /// `_` after that, or it can't be read back.
+    fn to_raw(&self) -> String;
+}
+
+pub fn serialize<T: HuonScalar, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(RAW_SCALAR, &value.to_raw())
+}
+
+pub fn deserialize<'de, T: HuonScalar, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    deserializer.deserialize_str(ScalarVisitor(PhantomData))
+}
+
+struct ScalarVisitor<T>(PhantomData<T>);
+
+impl<T: HuonScalar> de::Visitor<'_> for ScalarVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a scalar starting with `{}`", T::PREFIX)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        if !v.starts_with(T::PREFIX) {
+            return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+        }
+
+        T::from_raw(v).map_err(E::custom)
+    }
+}
+
+/// A fraction written as a percentage, `0.05` is written as `5%`. Reading `5%` without quotes
+/// needs [`DecoderOptions::percent_literals`](crate::DecoderOptions::percent_literals),
+/// strings like `"5%"` work either way. Other formats see a string.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Percent(pub f64);
+
+/// `5` to `0.05`, shifting the decimal point in text so no rounding error creeps in.
+pub(crate) fn percent_to_fraction(percent: f64) -> f64 {
+    // `Display` never uses an exponent, so there's no other one to clash with
+    format!("{percent}e-2").parse().unwrap_or(percent / 100.0)
+}
+
+fn fraction_to_percent(fraction: f64) -> f64 {
+    format!("{fraction}e2").parse().unwrap_or(fraction * 100.0)
+}
+
+impl Serialize for Percent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = format!("{}%", fraction_to_percent(self.0));
+        serializer.serialize_newtype_struct(RAW_SCALAR, &raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for Percent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PercentVisitor)
+    }
+}
+
+struct PercentVisitor;
+
+impl de::Visitor<'_> for PercentVisitor {
+    type Value = Percent;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a percentage like 5% or a fraction like 0.05")
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Percent, E> {
+        Ok(Percent(v))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Percent, E> {
+        Ok(Percent(v as f64))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Percent, E> {
+        v.strip_suffix('%')
+            .and_then(|percent| percent.trim().parse().ok())
+            .map(|percent| Percent(percent_to_fraction(percent)))
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::{HuonScalar, Percent};
+    use crate::{DecoderOptions, EncoderOptions, de::from_str, ser::to_string};
+
+    #[derive(Debug, PartialEq)]
+    struct Color {
+        r: u8,
+        g: u8,
+        b: u8,
+    }
+
+    impl HuonScalar for Color {
+        const PREFIX: char = '#';
+
+        fn from_raw(raw: &str) -> Result<Self, String> {
+            let hex = raw
+                .strip_prefix('#')
+                .filter(|hex| hex.len() == 6)
+                .ok_or_else(|| format!("`{raw}` isn't a color like #RRGGBB"))?;
+
+            let channel = |idx: usize| {
+                u8::from_str_radix(&hex[idx..idx + 2], 16).map_err(|err| err.to_string())
+            };
+
+            Ok(Color {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            })
+        }
+
+        fn to_raw(&self) -> String {
+            format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Theme {
+        name: String,
+        #[serde(with = "super")]
+        background: Color,
+    }
+
+    #[test]
+    fn test_hex_color() {
+        let options = DecoderOptions::default().with_scalar::<Color>();
+
+        let input = indoc! {r#"
+            # comments still work
+            name: "Magenta"
+            background: #FF00FF
+        "#};
+
+        let theme: Theme = from_str(input, options).unwrap();
+        let expected = Theme {
+            name: "Magenta".to_string(),
+            background: Color {
+                r: 255,
+                g: 0,
+                b: 255,
+            },
+        };
+        assert_eq!(theme, expected);
+
+        let s = to_string(&theme, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "name: \"Magenta\"\nbackground: #FF00FF\n");
+
+        let err = from_str::<Theme>("name: \"x\"\nbackground: #FF00", options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "background: `#FF00` isn't a color like #RRGGBB"
+        );
+
+        assert!(from_str::<Theme>(input, DecoderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_percent() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct PayRate {
+            monthly_increase: Percent,
+            bonus: Percent,
+        }
+
+        let options = DecoderOptions {
+            percent_literals: true,
+            ..Default::default()
+        };
+
+        let input = "monthly_increase: 5%\nbonus: 12.5%\n";
+        let rate: PayRate = from_str(input, options).unwrap();
+        assert_eq!(
+            rate,
+            PayRate {
+                monthly_increase: Percent(0.05),
+                bonus: Percent(0.125),
+            }
+        );
+        assert_eq!(to_string(&rate, EncoderOptions::default()).unwrap(), input);
+
+        // the way `test.huon` writes it
+        let rate: PayRate = from_str(
+            "monthly_increase: \"5%\"\nbonus: 0.125",
+            DecoderOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(rate.monthly_increase, Percent(0.05));
+        assert_eq!(rate.bonus, Percent(0.125));
+
+        assert!(from_str::<PayRate>(input, DecoderOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::value::{HuonKind, HuonValue};
+
+/// The keys a document's top-level object should have, and of which kind their values are.
+/// Checked with [`validate_schema`], which reports every problem instead of just the first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub kinds: HashMap<String, HuonKind>,
+    pub required: HashSet<String>,
+}
+
+impl Schema {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key that has to be there.
+    #[must_use]
+    pub fn required(mut self, key: impl Into<String>, kind: HuonKind) -> Self {
+        let key = key.into();
+        self.required.insert(key.clone());
+        self.kinds.insert(key, kind);
+        self
+    }
+
+    /// Adds a key that may be left out or be `null`.
+    #[must_use]
+    pub fn optional(mut self, key: impl Into<String>, kind: HuonKind) -> Self {
+        self.kinds.insert(key.into(), kind);
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    #[error("Expected an object, found {found:?}")]
+    NotAnObject { found: HuonKind },
+
+    #[error("Missing required key `{_0}`")]
+    MissingKey(String),
+
+    #[error("Expected `{key}` to be {expected:?}, found {found:?}")]
+    WrongType {
+        key: String,
+        expected: HuonKind,
+        found: HuonKind,
+    },
+}
+
+/// Checks `value` against `schema`, returning every mismatch sorted by key.
+/// Keys the schema doesn't know about are allowed.
+pub fn validate_schema(value: &HuonValue, schema: &Schema) -> Result<(), Vec<SchemaError>> {
+    let HuonValue::Object(map) = value else {
+        return Err(vec![SchemaError::NotAnObject {
+            found: value.kind(),
+        }]);
+    };
+
+    let mut keys: Vec<_> = schema.kinds.iter().collect();
+    keys.sort_unstable_by_key(|(key, _)| key.as_str());
+
+    let mut errors = Vec::new();
+    for (key, &expected) in keys {
+        match map.get(key.as_str()) {
+            None if schema.required.contains(key) => {
+                errors.push(SchemaError::MissingKey(key.clone()));
+            }
+            Some(HuonValue::Null) if !schema.required.contains(key) => {}
+            Some(value) if value.kind() != expected => errors.push(SchemaError::WrongType {
+                key: key.clone(),
+                expected,
+                found: value.kind(),
+            }),
+            _ => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use crate::DecoderOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let schema = Schema::new()
+            .required("name", HuonKind::String)
+            .required("age", HuonKind::Int)
+            .required("last_name", HuonKind::String)
+            .optional("nickname", HuonKind::String)
+            .optional("pay", HuonKind::Float);
+
+        let value = HuonValue::parse(
+            indoc! {r#"
+                name: "John"
+                age: "32"
+                nickname: null
+                job: "Software Engineer"
+            "#},
+            DecoderOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            validate_schema(&value, &schema),
+            Err(vec![
+                SchemaError::WrongType {
+                    key: "age".to_string(),
+                    expected: HuonKind::Int,
+                    found: HuonKind::String,
+                },
+                SchemaError::MissingKey("last_name".to_string()),
+            ])
+        );
+
+        let value = HuonValue::parse(
+            "name: \"John\"\nage: 32\nlast_name: \"Doe\"\npay: 4200.5",
+            DecoderOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(validate_schema(&value, &schema), Ok(()));
+
+        assert_eq!(
+            validate_schema(&HuonValue::Int(1), &schema),
+            Err(vec![SchemaError::NotAnObject {
+                found: HuonKind::Int
+            }])
+        );
+    }
+}
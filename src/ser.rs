@@ -1,8 +1,16 @@
 use serde::ser::{self, Serialize, SerializeMap, Serializer};
 use std::fmt::Display;
-use std::io;
+use std::{io, mem};
 
-use crate::{EncoderOptions, ListCommaStyle};
+use crate::{
+    EncoderOptions, IndentChar, ListCommaStyle, ListStyle,
+    parser::{Comments, owned::HuonValueOwned, value::HuonValue},
+    scalar, tokenizer,
+};
+
+/// A rough guess of how many bytes a single `key: value` line takes up.
+/// Used to pre-allocate the output when serializing maps into a `Vec`.
+const ESTIMATED_ENTRY_SIZE: usize = 16;
 
 #[derive(Debug, thiserror::Error)]
 pub enum HuonSerializeError {
@@ -24,12 +32,26 @@ impl From<io::Error> for HuonSerializeError {
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct HuonSerializer<W: io::Write> {
     writer: W,
     indent_level: usize,
     is_key: bool,
+    /// Writing the text of a custom scalar, which goes without quotes or backticks.
+    is_raw: bool,
     is_root: bool,
     key_pending: bool,
+    /// How many lists deep we currently are. Maps inside of lists are written in braces.
+    flow_depth: usize,
+    float_format: Option<fn(f64) -> String>,
+    reserve: fn(&mut W, usize),
+    comments: Comments,
+    /// The keys of the maps currently being written, only tracked when there are comments.
+    path: Vec<String>,
+    /// The column the value after the last key starts at, only tracked with a maximum width.
+    value_column: usize,
+    /// The struct fields left out through `skip_serializing_if`, in the order they were skipped.
+    skipped: Vec<&'static str>,
 
     options: EncoderOptions,
 }
@@ -40,18 +62,155 @@ impl<W: io::Write> HuonSerializer<W> {
             writer,
             indent_level: 0,
             is_key: false,
+            is_raw: false,
             is_root: true,
             key_pending: false,
+            flow_depth: 0,
+            float_format: None,
+            reserve: |_, _| {},
+            comments: Comments::new(),
+            path: Vec::new(),
+            value_column: 0,
+            skipped: Vec::new(),
             options,
         }
     }
 
+    /// Renders every `f32`/`f64` through `format` instead of the default `Display` output.
+    #[must_use]
+    pub fn with_float_format(mut self, format: fn(f64) -> String) -> Self {
+        self.float_format = Some(format);
+        self
+    }
+
+    /// Writes `comments` above the keys they belong to, as returned by
+    /// [`parse_with_comments`](crate::parser::parse_with_comments). Keys of maps written in
+    /// braces don't get their comments, since they don't have a line of their own.
+    #[must_use]
+    pub fn with_comments(mut self, comments: Comments) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// The names of the struct fields that were left out so far, like the ones
+    /// `#[serde(skip_serializing_if = "Option::is_none")]` skips.
+    #[must_use]
+    pub fn skipped_fields(&self) -> &[&'static str] {
+        &self.skipped
+    }
+
+    /// Finishes a whole document once its top-level value has been serialized.
+    /// This writes the final line break, see [`EncoderOptions::trailing_newline`].
+    pub fn end_document(&mut self) -> Result<(), HuonSerializeError> {
+        if self.options.trailing_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// A serializer writing into a buffer instead, in the same state as this one. It takes
+    /// over the comments while it's in use, [`HuonSerializer::join`] hands them back.
+    fn fork(&mut self) -> HuonSerializer<Vec<u8>> {
+        HuonSerializer {
+            writer: Vec::new(),
+            indent_level: self.indent_level,
+            is_key: false,
+            is_raw: false,
+            is_root: false,
+            key_pending: self.key_pending,
+            flow_depth: self.flow_depth,
+            float_format: self.float_format,
+            reserve: |vec, additional| vec.reserve(additional),
+            comments: mem::take(&mut self.comments),
+            path: mem::take(&mut self.path),
+            value_column: self.value_column,
+            skipped: mem::take(&mut self.skipped),
+            options: self.options,
+        }
+    }
+
+    /// Takes back what a [`HuonSerializer::fork`] borrowed, returning what it wrote.
+    fn join(&mut self, fork: HuonSerializer<Vec<u8>>) -> Vec<u8> {
+        self.comments = fork.comments;
+        self.path = fork.path;
+        self.skipped = fork.skipped;
+        fork.writer
+    }
+
+    /// Writes the comments belonging to `key` in the current map, one per line.
+    fn write_comments(&mut self, key: &str) -> Result<(), HuonSerializeError> {
+        self.path.push(unescape_key(key));
+
+        if let Some(lines) = self.comments.get(&self.path) {
+            let indent = self.indent();
+            for line in lines {
+                writeln!(self.writer, "{indent}# {line}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers where the value after `key` starts, see [`EncoderOptions::max_line_width`].
+    fn track_value_column<T: ?Sized + Serialize>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), HuonSerializeError> {
+        if self.options.max_line_width.is_some() {
+            let key = key_to_string(key, self.options)?;
+            let indent = self.indent_level * self.options.indent as usize;
+            self.value_column = indent + key.chars().count() + ": ".len();
+        }
+        Ok(())
+    }
+
+    /// The indentation of a line at the current level, see [`EncoderOptions::indent_char`].
+    fn indent(&self) -> String {
+        match self.options.indent_char {
+            IndentChar::Space => " ".repeat(self.indent_level * self.options.indent as usize),
+            IndentChar::Tab => "\t".repeat(self.indent_level),
+        }
+    }
+
     fn write_indent(&mut self) -> Result<(), HuonSerializeError> {
-        write!(
-            self.writer,
-            "{}",
-            " ".repeat(self.indent_level * self.options.indent as usize)
-        )?;
+        let indent = self.indent();
+        self.writer.write_all(indent.as_bytes())?;
+        Ok(())
+    }
+
+    /// Written before every entry of a list or a braced map.
+    fn write_flow_separator(&mut self, first: bool) -> Result<(), HuonSerializeError> {
+        match (self.options.list_style, self.options.list_comma_style) {
+            (ListStyle::Spaced, _) if first => Ok(()),
+            (ListStyle::Spaced, ListCommaStyle::None) => Ok(self.writer.write_all(b" ")?),
+            (ListStyle::Spaced, ListCommaStyle::Basic) => Ok(self.writer.write_all(b", ")?),
+            (ListStyle::Spaced, ListCommaStyle::Trailing) => Ok(self.writer.write_all(b",")?),
+            (ListStyle::Newline, comma_style) => {
+                if !first && comma_style != ListCommaStyle::None {
+                    self.writer.write_all(b",")?;
+                }
+                self.writer.write_all(b"\n")?;
+                self.write_indent()
+            }
+        }
+    }
+
+    /// Closes a list or a braced map, `indent_level` has to be restored already.
+    fn write_flow_end(&mut self, empty: bool, close: &[u8]) -> Result<(), HuonSerializeError> {
+        if !empty {
+            match (self.options.list_style, self.options.list_comma_style) {
+                (ListStyle::Spaced, ListCommaStyle::Trailing) => self.writer.write_all(b", ")?,
+                (ListStyle::Newline, comma_style) => {
+                    if comma_style == ListCommaStyle::Trailing {
+                        self.writer.write_all(b",")?;
+                    }
+                    self.writer.write_all(b"\n")?;
+                    self.write_indent()?;
+                }
+                (ListStyle::Spaced, _) => {}
+            }
+        }
+
+        self.writer.write_all(close)?;
         Ok(())
     }
 
@@ -93,7 +252,7 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         self.write_non_map_value_separator()?;
-        write!(self.writer, "{v}")?;
+        write!(self.writer, "{}", self.options.bool_style.word(v))?;
         Ok(())
     }
 
@@ -115,58 +274,110 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
         Ok(())
     }
 
+    /// As a map key, the number is escaped like `` `1`: ``, since a bare one isn't a key.
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
         self.write_non_map_value_separator()?;
-        write!(self.writer, "{v}")?;
+        if self.is_key {
+            write!(self.writer, "`{v}`")?;
+        } else {
+            write!(self.writer, "{v}")?;
+        }
         Ok(())
     }
 
-    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
-        Err(HuonSerializeError::Custom(
-            "Unsigned integers are not supported in Huon".to_string(),
-        ))
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
     }
 
-    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
-        Err(HuonSerializeError::Custom(
-            "Unsigned integers are not supported in Huon".to_string(),
-        ))
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
     }
 
-    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
-        Err(HuonSerializeError::Custom(
-            "Unsigned integers are not supported in Huon".to_string(),
-        ))
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
     }
 
-    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
-        Err(HuonSerializeError::Custom(
-            "Unsigned integers are not supported in Huon".to_string(),
-        ))
+    /// Values past `i64::MAX` are read back as [`HuonValue::BigInt`].
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    /// Values past `i128::MAX` can't be read back, so they are an error.
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        let Ok(v) = i128::try_from(v) else {
+            return Err(HuonSerializeError::Custom(format!(
+                "{v} is too large, only integers up to {} are supported in Huon",
+                i128::MAX
+            )));
+        };
+        self.serialize_i128(v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(f64::from(v))
     }
 
+    /// `NaN` and the infinities can't be read back, so they are an error, unless a
+    /// [`HuonSerializer::with_float_format`] function decides how to write them.
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        if self.float_format.is_none() && !v.is_finite() {
+            return Err(HuonSerializeError::Custom(format!(
+                "{v} is not supported in Huon, only finite floats are"
+            )));
+        }
+
         self.write_non_map_value_separator()?;
-        write!(self.writer, "{v}")?;
+        match self.float_format {
+            Some(format) => self.writer.write_all(format(v).as_bytes())?,
+            None => match self.options.float_precision {
+                // keep the `.0` so the value is read back as a float, not an int
+                Some(0) => write!(self.writer, "{v:.0}.0")?,
+                Some(precision) => write!(self.writer, "{v:.precision$}")?,
+                None if v.fract() == 0.0 => write!(self.writer, "{v}.0")?,
+                None => write!(self.writer, "{v}")?,
+            },
+        }
         Ok(())
     }
 
+    /// Written as a string holding just the char, see [`EncoderOptions::bare_chars`].
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        if !self.options.bare_chars {
+            return self.serialize_str(v.encode_utf8(&mut [0; 4]));
+        }
+
         self.write_non_map_value_separator()?;
         write!(self.writer, "{v}")?;
         Ok(())
     }
 
+    /// Keys that would be read as something else, like `my key` or `1abc`, are written in
+    /// backticks. Keys with a backtick or a line break in them can't be written at all, and
+    /// neither can strings containing the [`EncoderOptions::quote_style`] character.
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
         self.write_non_map_value_separator()?;
-        if self.is_key {
+        if self.is_raw || (self.is_key && tokenizer::is_plain_key(v)) {
             write!(self.writer, "{v}")?;
+        } else if self.is_key {
+            if v.contains(['`', '\n']) {
+                return Err(HuonSerializeError::Custom(format!(
+                    "The key {v:?} can't be written in Huon"
+                )));
+            }
+            write!(self.writer, "`{v}`")?;
         } else {
-            write!(self.writer, "\"{v}\"")?;
+            let quote = self.options.quote_style.char();
+            if v.contains(quote) {
+                return Err(HuonSerializeError::Custom(format!(
+                    "The string {v:?} contains the quote character {quote}, pick the other \
+                     QuoteStyle to write it"
+                )));
+            }
+            write!(self.writer, "{quote}{v}{quote}")?;
         }
         Ok(())
     }
@@ -190,10 +401,9 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
         value.serialize(self)
     }
 
+    /// Written as `null`, which is how `serde_json::Value::Null` serializes itself.
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Err(HuonSerializeError::Custom(
-            "Unit is not supported in huon".to_string(),
-        ))
+        self.serialize_none()
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
@@ -215,35 +425,64 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
+        if name == scalar::RAW_SCALAR {
+            let raw = raw_to_string(value, self.options)?;
+            self.write_non_map_value_separator()?;
+            self.writer.write_all(raw.as_bytes())?;
+            return Ok(());
+        }
+
         value.serialize(self)
     }
 
+    /// Written as an object with the variant as its only key, like `Ok: 5`.
     fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        let mut map = self.serialize_map(Some(1))?;
+        map.serialize_entry(variant, value)?;
+        SerializeMap::end(map)
     }
 
-    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         self.write_non_map_value_separator()?;
-        Ok(HuonSeqSerializer::new(
-            self,
-            len,
-            self.options.list_comma_style,
-        ))
+        self.writer.write_all(b"[")?;
+
+        let column = if self.flow_depth == 0 {
+            self.value_column
+        } else {
+            self.indent_level * self.options.indent as usize
+        };
+
+        self.is_root = false;
+        self.flow_depth += 1;
+        self.indent_level += 1;
+
+        let width = match self.options.max_line_width {
+            Some(width) if self.options.list_style == ListStyle::Spaced => {
+                Some(width.saturating_sub(column))
+            }
+            _ => None,
+        };
+
+        if width.is_some() || self.options.dedup_lists {
+            Ok(HuonSeqSerializer::new_held_back(self, width))
+        } else {
+            Ok(HuonSeqSerializer::new(self))
+        }
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -274,9 +513,31 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
         ))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        // an empty nested map has no lines to indent, so it's written as `{}`
+        let is_empty = len == Some(0) && !self.is_root;
+
+        if self.flow_depth > 0 || is_empty {
+            self.write_non_map_value_separator()?;
+            self.writer.write_all(b"{")?;
+            self.indent_level += 1;
+
+            return Ok(HuonMapSerializer::new_flow(self));
+        }
+
+        if self.options.inline_single_field && len == Some(1) && !self.is_root {
+            // the line break is only written once it's known the value isn't a scalar
+            self.indent_level += 1;
+            return Ok(HuonMapSerializer::new_single(self));
+        }
+
         self.write_map_value_separator()?;
 
+        if let Some(len) = len {
+            let indent = self.indent_level * self.options.indent as usize;
+            (self.reserve)(&mut self.writer, len * (indent + ESTIMATED_ENTRY_SIZE));
+        }
+
         if self.is_root {
             self.is_root = false;
         } else {
@@ -311,25 +572,203 @@ impl<'a, W: io::Write> Serializer for &'a mut HuonSerializer<W> {
 pub struct HuonMapSerializer<'a, W: io::Write> {
     ser: &'a mut HuonSerializer<W>,
     first: bool,
+    flow: bool,
+    /// With [`EncoderOptions::align_values`], every key and its serialized value (starting
+    /// at the colon) is held back until the widest key is known.
+    aligned: Option<Vec<(String, Vec<u8>)>>,
+    /// With [`EncoderOptions::inline_single_field`], the key of a map with a single entry,
+    /// which is held back until it's known whether the value fits in braces.
+    single: Option<String>,
 }
 
 impl<'a, W: io::Write> HuonMapSerializer<'a, W> {
     pub fn new(ser: &'a mut HuonSerializer<W>) -> HuonMapSerializer<'a, W> {
-        HuonMapSerializer { ser, first: true }
+        let aligned = ser.options.align_values.then(Vec::new);
+
+        HuonMapSerializer {
+            ser,
+            first: true,
+            flow: false,
+            aligned,
+            single: None,
+        }
+    }
+
+    /// A map with a single entry, see [`EncoderOptions::inline_single_field`].
+    fn new_single(ser: &'a mut HuonSerializer<W>) -> HuonMapSerializer<'a, W> {
+        HuonMapSerializer {
+            ser,
+            first: true,
+            flow: false,
+            aligned: None,
+            single: Some(String::new()),
+        }
+    }
+
+    /// Writes the entry of a single entry map, in braces if `value` is a scalar.
+    fn write_single<T: ?Sized + Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), HuonSerializeError> {
+        // nested comments are looked up by the path, which has to include this key
+        let has_comments = !self.ser.comments.is_empty();
+        if has_comments {
+            self.ser.path.push(unescape_key(key));
+        }
+
+        // the value is written into a buffer first, to tell whether it's a scalar
+        let mut fork = self.ser.fork();
+        fork.key_pending = true;
+        let result = value.serialize(&mut fork);
+        let buf = self.ser.join(fork);
+
+        if has_comments {
+            self.ser.path.pop();
+        }
+        result?;
+
+        self.ser.write_map_value_separator()?;
+
+        match buf.strip_prefix(b": ") {
+            Some(rest) if !rest.starts_with(b"[") && !rest.starts_with(b"{") => {
+                write!(self.ser.writer, " {{ {key}: ")?;
+                self.ser.writer.write_all(rest)?;
+                self.ser.writer.write_all(b" }")?;
+                Ok(())
+            }
+            _ => {
+                self.ser.writer.write_all(b"\n")?;
+
+                if has_comments {
+                    self.ser.write_comments(key)?;
+                    self.ser.path.pop();
+                }
+
+                self.ser.write_indent()?;
+                self.ser.writer.write_all(key.as_bytes())?;
+                self.ser.writer.write_all(&buf)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// A map inside of a list, written in braces: `{ name: "IT" }`.
+    fn new_flow(ser: &'a mut HuonSerializer<W>) -> HuonMapSerializer<'a, W> {
+        HuonMapSerializer {
+            ser,
+            first: true,
+            flow: true,
+            aligned: None,
+            single: None,
+        }
+    }
+
+    /// Writes the held back entries, padding every value to start in the same column.
+    /// Values of nested maps start on the next line, so they aren't padded.
+    fn write_aligned(&mut self, entries: Vec<(String, Vec<u8>)>) -> Result<(), HuonSerializeError> {
+        let width = entries
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        for (idx, (key, value)) in entries.into_iter().enumerate() {
+            if idx > 0 {
+                self.ser.writer.write_all(b"\n")?;
+            }
+
+            if !self.ser.comments.is_empty() {
+                self.ser.write_comments(&key)?;
+                self.ser.path.pop();
+            }
+
+            self.ser.write_indent()?;
+            self.ser.writer.write_all(key.as_bytes())?;
+
+            match value.strip_prefix(b": ") {
+                Some(rest) => {
+                    let padding = width - key.chars().count();
+                    write!(self.ser.writer, ": {}", " ".repeat(padding))?;
+                    self.ser.writer.write_all(rest)?;
+                }
+                None => self.ser.writer.write_all(&value)?,
+            }
+        }
+        Ok(())
     }
 }
 
+/// Serializes a map key on its own, the way it's written in front of the colon.
+fn key_to_string<T: ?Sized + Serialize>(
+    key: &T,
+    options: EncoderOptions,
+) -> Result<String, HuonSerializeError> {
+    let mut buf = Vec::new();
+    let mut key_ser = HuonSerializer::new(&mut buf, options);
+    key_ser.is_key = true;
+    key.serialize(&mut key_ser)?;
+
+    String::from_utf8(buf).map_err(|e| HuonSerializeError::Custom(e.to_string()))
+}
+
+/// Writes the text of a custom scalar, see [`scalar::HuonScalar`].
+fn raw_to_string<T: ?Sized + Serialize>(
+    value: &T,
+    options: EncoderOptions,
+) -> Result<String, HuonSerializeError> {
+    let mut buf = Vec::new();
+    let mut raw_ser = HuonSerializer::new(&mut buf, options);
+    raw_ser.is_raw = true;
+    value.serialize(&mut raw_ser)?;
+
+    String::from_utf8(buf).map_err(|e| HuonSerializeError::Custom(e.to_string()))
+}
+
+/// A key as the parser reads it, without the backticks it may be written in.
+fn unescape_key(key: &str) -> String {
+    key.strip_prefix('`')
+        .and_then(|key| key.strip_suffix('`'))
+        .unwrap_or(key)
+        .to_string()
+}
+
 impl<W: io::Write> SerializeMap for HuonMapSerializer<'_, W> {
     type Ok = ();
     type Error = HuonSerializeError;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
-        if !self.first {
-            self.ser.writer.write_all(b"\n")?;
+        if let Some(single) = &mut self.single {
+            *single = key_to_string(key, self.ser.options)?;
+            return Ok(());
         }
-        self.first = false;
 
-        self.ser.write_indent()?;
+        if let Some(entries) = &mut self.aligned {
+            entries.push((key_to_string(key, self.ser.options)?, Vec::new()));
+            self.ser.track_value_column(key)?;
+            self.ser.key_pending = true;
+            return Ok(());
+        }
+
+        if self.flow {
+            if self.first && self.ser.options.list_style == ListStyle::Spaced {
+                self.ser.writer.write_all(b" ")?;
+            }
+            self.ser.write_flow_separator(self.first)?;
+        } else {
+            if !self.first {
+                self.ser.writer.write_all(b"\n")?;
+            }
+
+            if !self.ser.comments.is_empty() {
+                let key = key_to_string(key, self.ser.options)?;
+                self.ser.write_comments(&key)?;
+            }
+
+            self.ser.track_value_column(key)?;
+            self.ser.write_indent()?;
+        }
+        self.first = false;
 
         self.ser.is_key = true;
         key.serialize(&mut *self.ser)?;
@@ -340,13 +779,53 @@ impl<W: io::Write> SerializeMap for HuonMapSerializer<'_, W> {
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
-        value.serialize(&mut *self.ser)
+        if let Some(key) = self.single.take() {
+            return self.write_single(&key, value);
+        }
+
+        if let Some(entries) = &mut self.aligned {
+            let (key, buf) = entries.last_mut().expect("a key before every value");
+
+            // nested comments are looked up by the path, which has to include this key
+            let track_path = !self.ser.comments.is_empty();
+            if track_path {
+                self.ser.path.push(unescape_key(key));
+            }
+
+            let mut fork = self.ser.fork();
+            let result = value.serialize(&mut fork);
+            *buf = self.ser.join(fork);
+            self.ser.key_pending = false;
+
+            if track_path {
+                self.ser.path.pop();
+            }
+            return result;
+        }
+
+        value.serialize(&mut *self.ser)?;
+
+        if !self.flow && !self.ser.comments.is_empty() {
+            self.ser.path.pop();
+        }
+        Ok(())
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if let Some(entries) = self.aligned.take() {
+            self.write_aligned(entries)?;
+        }
+
         if self.ser.indent_level > 0 {
             self.ser.indent_level -= 1;
         }
+
+        if self.flow {
+            if !self.first && self.ser.options.list_style == ListStyle::Spaced {
+                self.ser.writer.write_all(b" ")?;
+            }
+            self.ser.write_flow_end(self.first, b"}")?;
+        }
         Ok(())
     }
 }
@@ -364,6 +843,11 @@ impl<W: io::Write> ser::SerializeStruct for HuonMapSerializer<'_, W> {
         self.serialize_value(value)
     }
 
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.ser.skipped.push(key);
+        Ok(())
+    }
+
     fn end(self) -> Result<Self::Ok, Self::Error> {
         SerializeMap::end(self)
     }
@@ -372,28 +856,90 @@ impl<W: io::Write> ser::SerializeStruct for HuonMapSerializer<'_, W> {
 pub struct HuonSeqSerializer<'a, W: io::Write> {
     ser: &'a mut HuonSerializer<W>,
     first: bool,
-    length: Option<usize>,
-    idx: usize,
-    style: ListCommaStyle,
+    /// With [`EncoderOptions::max_line_width`] or [`EncoderOptions::dedup_lists`], the
+    /// serialized elements are held back until the whole list is known.
+    held_back: Option<Vec<Vec<u8>>>,
+    /// With [`EncoderOptions::dedup_lists`], the value of every held back element that's a
+    /// scalar, which the elements are sorted by.
+    sort_keys: Vec<Option<HuonValue<'static>>>,
+    /// The columns the list has to fit into to stay on one line.
+    width: Option<usize>,
 }
 
 impl<'a, W: io::Write> HuonSeqSerializer<'a, W> {
-    pub fn new(
+    pub fn new(ser: &'a mut HuonSerializer<W>) -> HuonSeqSerializer<'a, W> {
+        HuonSeqSerializer {
+            ser,
+            first: true,
+            held_back: None,
+            sort_keys: Vec::new(),
+            width: None,
+        }
+    }
+
+    /// A list that's only written once it's complete. It's written one entry per line if it
+    /// doesn't fit into `width` columns.
+    fn new_held_back(
         ser: &'a mut HuonSerializer<W>,
-        length: Option<usize>,
-        style: ListCommaStyle,
+        width: Option<usize>,
     ) -> HuonSeqSerializer<'a, W> {
         HuonSeqSerializer {
             ser,
             first: true,
-            length,
-            idx: 0,
-            style,
+            held_back: Some(Vec::new()),
+            sort_keys: Vec::new(),
+            width,
+        }
+    }
+
+    /// Sorts and deduplicates the held back elements by their sort keys, see
+    /// [`EncoderOptions::dedup_lists`]. Lists that can't be sorted are left as they are.
+    fn dedup(&mut self, elements: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        let keys = mem::take(&mut self.sort_keys);
+        let Some(values) = keys.iter().cloned().collect::<Option<Vec<_>>>() else {
+            return elements;
+        };
+
+        let mut list = HuonValue::List(values);
+        if !list.sort_list() {
+            return elements;
         }
+
+        let HuonValue::List(mut values) = list else {
+            unreachable!("sort_list only sorts lists");
+        };
+        values.dedup();
+
+        // equal scalars are written the same, so any element with the key will do
+        values
+            .iter()
+            .map(|value| {
+                let idx = keys
+                    .iter()
+                    .position(|key| key.as_ref() == Some(value))
+                    .expect("every sorted value is one of the keys");
+                elements[idx].clone()
+            })
+            .collect()
     }
 
-    fn is_last(&self) -> bool {
-        self.length == Some(self.idx + 1)
+    /// Lays out the held back elements in `style`, everything after the `[`.
+    fn layout(
+        &mut self,
+        elements: &[Vec<u8>],
+        style: ListStyle,
+    ) -> Result<Vec<u8>, HuonSerializeError> {
+        let mut fork = self.ser.fork();
+        fork.options.list_style = style;
+
+        for (idx, element) in elements.iter().enumerate() {
+            fork.write_flow_separator(idx == 0)?;
+            fork.writer.extend_from_slice(element);
+        }
+        fork.indent_level -= 1;
+        fork.write_flow_end(elements.is_empty(), b"]")?;
+
+        Ok(self.ser.join(fork))
     }
 }
 
@@ -402,78 +948,365 @@ impl<W: io::Write> ser::SerializeSeq for HuonSeqSerializer<'_, W> {
     type Error = HuonSerializeError;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
-        if self.first {
-            write!(self.ser.writer, "[")?;
-            self.first = false;
+        if let Some(elements) = &mut self.held_back {
+            if self.ser.options.dedup_lists {
+                let key = value.serialize(SortKeySerializer(self.ser.options)).ok();
+                self.sort_keys.push(key);
+            }
+
+            let mut fork = self.ser.fork();
+            let result = value.serialize(&mut fork);
+            elements.push(self.ser.join(fork));
+            return result;
         }
 
-        value.serialize(&mut *self.ser)?;
+        self.ser.write_flow_separator(self.first)?;
+        self.first = false;
 
-        match self.style {
-            ListCommaStyle::None if !self.is_last() => write!(self.ser.writer, " ")?,
-            ListCommaStyle::Basic if !self.is_last() => write!(self.ser.writer, ", ")?,
-            ListCommaStyle::Trailing => {
-                if self.is_last() {
-                    write!(self.ser.writer, ", ")?;
-                } else {
-                    write!(self.ser.writer, ",")?;
-                }
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if let Some(mut elements) = self.held_back.take() {
+            if self.ser.options.dedup_lists {
+                elements = self.dedup(elements);
             }
-            _ => (),
+
+            let mut list = self.layout(&elements, self.ser.options.list_style)?;
+
+            // `[` is already written. If a nested list was wrapped, this one is as well
+            if let Some(width) = self.width
+                && (1 + list.len() > width || list.contains(&b'\n'))
+            {
+                list = self.layout(&elements, ListStyle::Newline)?;
+            }
+
+            self.ser.indent_level -= 1;
+            self.ser.flow_depth -= 1;
+            self.ser.writer.write_all(&list)?;
+            return Ok(());
         }
 
-        self.idx += 1;
+        self.ser.indent_level -= 1;
+        self.ser.flow_depth -= 1;
 
-        Ok(())
+        self.ser.write_flow_end(self.first, b"]")
     }
+}
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        write!(self.ser.writer, "]")?;
+/// Turns a scalar into the value it's written as, for [`EncoderOptions::dedup_lists`] to sort
+/// by. Anything else, like a list or an object, is an error.
+struct SortKeySerializer(EncoderOptions);
 
-        Ok(())
+impl SortKeySerializer {
+    fn not_a_scalar<T>() -> Result<T, HuonSerializeError> {
+        Err(HuonSerializeError::Custom("Not a scalar".to_string()))
     }
 }
 
-pub fn to_string<T>(value: &T, options: EncoderOptions) -> Result<String, HuonSerializeError>
-where
-    T: ?Sized + Serialize,
-{
-    let mut vec = Vec::new();
-    let mut serializer = HuonSerializer {
-        writer: &mut vec,
-        indent_level: 0,
-        is_key: false,
-        is_root: true,
-        key_pending: false,
-        options,
-    };
+impl Serializer for SortKeySerializer {
+    type Ok = HuonValue<'static>;
+    type Error = HuonSerializeError;
 
-    value.serialize(&mut serializer)?;
+    type SerializeSeq = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeTuple = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeMap = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeStruct = ser::Impossible<Self::Ok, HuonSerializeError>;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, HuonSerializeError>;
 
-    String::from_utf8(vec).map_err(|e| HuonSerializeError::Custom(e.to_string()))
-}
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(HuonValue::Boolean(v))
+    }
 
-#[cfg(test)]
-mod tests {
-    use indoc::indoc;
-    use pretty_assertions::assert_eq;
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
 
-    use crate::{
-        ListCommaStyle,
-        test_list_model::CodeInfo,
-        test_model::{Job, JobCategory, JobInfo, NewType, PayRate, Person},
-    };
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
 
-    use super::*;
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
 
-    #[test]
-    fn test_serialize_struct() {
-        let expected_person = Person {
-            name: "John",
-            last_name: "Doe",
-            age: 32,
-            job1: Job {
-                category: JobCategory {
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(HuonValue::Int(v))
+    }
+
+    /// Like the parser, this only uses [`HuonValue::BigInt`] for values that need it.
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(i64::try_from(v).map_or(HuonValue::BigInt(v), HuonValue::Int))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        i128::try_from(v).map_or_else(|_| Self::not_a_scalar(), |v| self.serialize_i128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    /// Rounded to [`EncoderOptions::float_precision`], so floats written alike are equal.
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        let v = match self.0.float_precision {
+            Some(precision) => format!("{v:.precision$}").parse().unwrap_or(v),
+            None => v,
+        };
+        Ok(HuonValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(HuonValue::String(v.to_string().into()))
+    }
+
+    /// Also used for the text of custom scalars, which are sorted like strings.
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(HuonValue::String(v.to_string().into()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(HuonValue::Null)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(i64::from(variant_index))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Self::not_a_scalar()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Self::not_a_scalar()
+    }
+}
+
+impl Serialize for HuonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            HuonValue::String(s) => serializer.serialize_str(s),
+            HuonValue::Int(i) => serializer.serialize_i64(*i),
+            HuonValue::BigInt(i) => serializer.serialize_i128(*i),
+            HuonValue::Float(f) => serializer.serialize_f64(*f),
+            HuonValue::Boolean(b) => serializer.serialize_bool(*b),
+            HuonValue::Null => serializer.serialize_none(),
+            HuonValue::List(list) => list.serialize(serializer),
+            HuonValue::Object(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for HuonValueOwned {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            HuonValueOwned::String(s) => serializer.serialize_str(s),
+            HuonValueOwned::Int(i) => serializer.serialize_i64(*i),
+            HuonValueOwned::BigInt(i) => serializer.serialize_i128(*i),
+            HuonValueOwned::Float(f) => serializer.serialize_f64(*f),
+            HuonValueOwned::Boolean(b) => serializer.serialize_bool(*b),
+            HuonValueOwned::Null => serializer.serialize_none(),
+            HuonValueOwned::List(list) => list.serialize(serializer),
+            HuonValueOwned::Object(map) => map.serialize(serializer),
+        }
+    }
+}
+
+pub fn to_string<T>(value: &T, options: EncoderOptions) -> Result<String, HuonSerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    to_string_with_comments(value, Comments::new(), options)
+}
+
+/// Serializes `value` like [`to_string`], but with the values of sibling keys lined up:
+///
+/// ```text
+/// name:      "John"
+/// age:       32
+/// last_name: "Doe"
+/// ```
+pub fn to_string_pretty<T>(value: &T, options: EncoderOptions) -> Result<String, HuonSerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let options = EncoderOptions {
+        align_values: true,
+        ..options
+    };
+    to_string(value, options)
+}
+
+/// Serializes `value` like [`to_string`], also returning the names of the struct fields that
+/// were left out, see [`HuonSerializer::skipped_fields`].
+pub fn to_string_with_skipped<T>(
+    value: &T,
+    options: EncoderOptions,
+) -> Result<(String, Vec<&'static str>), HuonSerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let mut vec = Vec::new();
+    let mut serializer = HuonSerializer::new(&mut vec, options);
+    serializer.reserve = |vec, additional| vec.reserve(additional);
+
+    value.serialize(&mut serializer)?;
+    serializer.end_document()?;
+    let skipped = mem::take(&mut serializer.skipped);
+
+    let s = String::from_utf8(vec).map_err(|e| HuonSerializeError::Custom(e.to_string()))?;
+    Ok((s, skipped))
+}
+
+/// Serializes `value` like [`to_string`], writing `comments` above the keys they belong to.
+pub fn to_string_with_comments<T>(
+    value: &T,
+    comments: Comments,
+    options: EncoderOptions,
+) -> Result<String, HuonSerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let mut vec = Vec::new();
+    let mut serializer = HuonSerializer::new(&mut vec, options).with_comments(comments);
+    serializer.reserve = |vec, additional| vec.reserve(additional);
+
+    value.serialize(&mut serializer)?;
+    serializer.end_document()?;
+
+    String::from_utf8(vec).map_err(|e| HuonSerializeError::Custom(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    use crate::{
+        BoolStyle, DecoderOptions, ListCommaStyle, ListStyle, QuoteStyle,
+        de::from_str,
+        test_list_model::CodeInfo,
+        test_model::{Job, JobCategory, JobInfo, NewType, PayRate, Person},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_struct() {
+        let expected_person = Person {
+            name: "John",
+            last_name: "Doe",
+            age: 32,
+            job1: Job {
+                category: JobCategory {
                     name: NewType("IT"),
                 },
                 info: JobInfo {
@@ -504,7 +1337,8 @@ mod tests {
 
         let s = to_string(&expected_person, EncoderOptions::default()).unwrap();
 
-        let expected = include_str!("../test.huon");
+        // `pay` is a float, so it keeps its `.0` to be read back as one
+        let expected = include_str!("../test.huon").replace("pay: 3700", "pay: 3700.0");
 
         assert_eq!(s, expected);
     }
@@ -518,6 +1352,7 @@ mod tests {
             EncoderOptions {
                 indent: 4,
                 list_comma_style: ListCommaStyle::Basic,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -538,6 +1373,7 @@ mod tests {
             EncoderOptions {
                 indent: 2,
                 list_comma_style: ListCommaStyle::Basic,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -548,7 +1384,8 @@ mod tests {
             test_codes:
               codes: [111.1, 333.3, 555.5]
               info: "Passwords"
-            name: "General Access""#};
+            name: "General Access"
+            "#};
 
         assert_eq!(s, expected);
     }
@@ -562,6 +1399,7 @@ mod tests {
             EncoderOptions {
                 indent: 4,
                 list_comma_style: ListCommaStyle::None,
+                ..Default::default()
             },
         )
         .unwrap();
@@ -572,8 +1410,1184 @@ mod tests {
         test_codes:
             codes: [111.1 333.3 555.5]
             info: "Passwords"
+        name: "General Access"
+        "#};
+
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn test_custom_float_format() {
+        let code_info = CodeInfo::default();
+
+        let mut buf = Vec::new();
+        let mut serializer = HuonSerializer::new(&mut buf, EncoderOptions::default())
+            .with_float_format(|f| format!("{f:.2}"));
+
+        code_info.serialize(&mut serializer).unwrap();
+
+        let expected = indoc! {r#"
+        test_codes:
+            codes: [111.10 333.30 555.50]
+            info: "Passwords"
         name: "General Access""#};
 
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_single_quoted_strings() {
+        #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+        struct Quote {
+            text: String,
+        }
+
+        let quote = Quote {
+            text: r#"she said "hi""#.to_string(),
+        };
+
+        let s = to_string(
+            &quote,
+            EncoderOptions {
+                quote_style: QuoteStyle::Single,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(s, "text: 'she said \"hi\"'\n");
+
+        let parsed: Quote = from_str(
+            &s,
+            DecoderOptions {
+                quote_style: QuoteStyle::Single,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(parsed, quote);
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct Member {
+        name: String,
+        pay: i64,
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct Team {
+        members: Vec<Member>,
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct Company {
+        team: Team,
+        name: String,
+    }
+
+    fn company() -> Company {
+        Company {
+            team: Team {
+                members: vec![
+                    Member {
+                        name: "a".to_string(),
+                        pay: 1,
+                    },
+                    Member {
+                        name: "b".to_string(),
+                        pay: 2,
+                    },
+                ],
+            },
+            name: "c".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_seq_of_structs_spaced() {
+        let s = to_string(&company(), EncoderOptions::default()).unwrap();
+
+        let expected = indoc! {r#"
+        team:
+            members: [{ name: "a" pay: 1 } { name: "b" pay: 2 }]
+        name: "c"
+        "#};
+
+        assert_eq!(s, expected);
+
+        let parsed: Company = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, company());
+    }
+
+    #[test]
+    fn test_seq_of_structs_newline() {
+        let s = to_string(
+            &company(),
+            EncoderOptions {
+                list_style: ListStyle::Newline,
+                list_comma_style: ListCommaStyle::Basic,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let expected = indoc! {r#"
+        team:
+            members: [
+                {
+                    name: "a",
+                    pay: 1
+                },
+                {
+                    name: "b",
+                    pay: 2
+                }
+            ]
+        name: "c"
+        "#};
+
         assert_eq!(s, expected);
+
+        let parsed: Company = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, company());
+    }
+
+    #[test]
+    fn test_seq_newline() {
+        let s = to_string(
+            &CodeInfo::default(),
+            EncoderOptions {
+                list_style: ListStyle::Newline,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let expected = indoc! {r#"
+        test_codes:
+            codes: [
+                111.1
+                333.3
+                555.5
+            ]
+            info: "Passwords"
+        name: "General Access"
+        "#};
+
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn test_serialize_empty_containers() {
+        let mut map = BTreeMap::new();
+        map.insert("list", HuonValueOwned::List(vec![]));
+        map.insert("object", HuonValueOwned::Object(BTreeMap::new()));
+
+        let s = to_string(&map, EncoderOptions::default()).unwrap();
+
+        assert_eq!(s, "list: []\nobject: {}\n");
+    }
+
+    fn arb_key() -> impl Strategy<Value = String> {
+        prop_oneof![
+            8 => "[a-z_][a-z0-9_]{0,8}",
+            // need backticks, or can't be written at all
+            1 => "[a-z0-9_ :#`\n-]{0,8}",
+        ]
+    }
+
+    fn arb_value() -> impl Strategy<Value = HuonValueOwned> {
+        use proptest::num::f64;
+
+        let leaf = prop_oneof![
+            8 => "[a-zA-Z0-9 _.,:%-]{0,12}".prop_map(HuonValueOwned::String),
+            // may hold the quote char
+            1 => "[a-z #`'\"\n]{0,12}".prop_map(HuonValueOwned::String),
+            8 => any::<i64>().prop_map(HuonValueOwned::Int),
+            8 => (f64::NORMAL | f64::SUBNORMAL | f64::ZERO | f64::POSITIVE | f64::NEGATIVE)
+                .prop_map(HuonValueOwned::Float),
+            1 => (f64::QUIET_NAN | f64::INFINITE).prop_map(HuonValueOwned::Float),
+            8 => any::<bool>().prop_map(HuonValueOwned::Boolean),
+            8 => Just(HuonValueOwned::Null),
+        ];
+
+        leaf.prop_recursive(4, 48, 6, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..6).prop_map(HuonValueOwned::List),
+                prop::collection::btree_map(arb_key(), inner, 0..6)
+                    .prop_map(HuonValueOwned::Object),
+            ]
+        })
+    }
+
+    fn arb_document() -> impl Strategy<Value = HuonValueOwned> {
+        prop::collection::btree_map(arb_key(), arb_value(), 0..8).prop_map(HuonValueOwned::Object)
+    }
+
+    /// Whether the serializer can write `value` in a way that reads back as the same value.
+    fn representable(value: &HuonValueOwned) -> bool {
+        match value {
+            HuonValueOwned::String(s) => !s.contains('"'),
+            HuonValueOwned::Float(f) => f.is_finite(),
+            HuonValueOwned::List(list) => list.iter().all(representable),
+            HuonValueOwned::Object(map) => map
+                .iter()
+                .all(|(key, value)| !key.contains(['`', '\n']) && representable(value)),
+            _ => true,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(
+            document in arb_document(),
+            list_style in prop_oneof![Just(ListStyle::Spaced), Just(ListStyle::Newline)],
+            list_comma_style in prop_oneof![
+                Just(ListCommaStyle::None),
+                Just(ListCommaStyle::Basic),
+                Just(ListCommaStyle::Trailing),
+            ],
+        ) {
+            let options = EncoderOptions {
+                list_comma_style,
+                list_style,
+                ..Default::default()
+            };
+            let s = match to_string(&document, options) {
+                Ok(s) => s,
+                Err(e) => {
+                    prop_assert!(!representable(&document), "{}", e);
+                    return Ok(());
+                }
+            };
+            prop_assert!(representable(&document), "wrote:\n{}", s);
+
+            let parsed = HuonValue::parse(&s, DecoderOptions::default())
+                .map_err(|e| TestCaseError::fail(format!("{e} in:\n{s}")))?;
+
+            prop_assert_eq!(HuonValueOwned::from(parsed), document, "in:\n{}", s);
+        }
+    }
+
+    #[test]
+    fn test_reformat_with_comments() {
+        let input = indoc! {r#"
+            # who
+            name: "John"
+            job:
+                # per month
+                pay: 4200
+                title: "Software Engineer"
+            # years
+            age: 32"#};
+
+        let (map, comments) =
+            crate::parser::parse_with_comments(input, DecoderOptions::default()).unwrap();
+        let s = to_string_with_comments(
+            &HuonValue::Object(map).sorted(),
+            comments,
+            EncoderOptions::default(),
+        )
+        .unwrap();
+
+        let expected = indoc! {r#"
+            # years
+            age: 32
+            job:
+                # per month
+                pay: 4200
+                title: "Software Engineer"
+            # who
+            name: "John"
+            "#};
+
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_float_precision() {
+        #[derive(serde::Serialize)]
+        struct Circle {
+            pi: f64,
+            radius: f64,
+            area: f64,
+        }
+
+        let circle = Circle {
+            pi: 3.14159,
+            radius: 2.0,
+            area: 12.566_370_614,
+        };
+
+        let options = |float_precision| EncoderOptions {
+            float_precision,
+            ..Default::default()
+        };
+
+        let s = to_string(&circle, options(Some(2))).unwrap();
+        assert_eq!(s, "pi: 3.14\nradius: 2.00\narea: 12.57\n");
+
+        let s = to_string(&circle, options(Some(0))).unwrap();
+        assert_eq!(s, "pi: 3.0\nradius: 2.0\narea: 13.0\n");
+
+        let s = to_string(&circle, options(None)).unwrap();
+        assert_eq!(s, "pi: 3.14159\nradius: 2.0\narea: 12.566370614\n");
+    }
+
+    #[test]
+    fn test_trailing_newline() {
+        let with = to_string(&CodeInfo::default(), EncoderOptions::default()).unwrap();
+        let without = to_string(
+            &CodeInfo::default(),
+            EncoderOptions {
+                trailing_newline: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(with, format!("{without}\n"));
+        assert!(!without.ends_with('\n'));
+
+        let parsed: CodeInfo = from_str(&with, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, CodeInfo::default());
+    }
+
+    #[test]
+    fn test_serialize_embedded_value() {
+        #[derive(serde::Serialize)]
+        struct Config<'a> {
+            name: &'a str,
+            extra: HuonValue<'a>,
+            version: i64,
+        }
+
+        // a single key per object, so the order of the written keys is fixed
+        let extra = HuonValue::parse(
+            indoc! {r#"
+                db:
+                    hosts: ["a" "b"]
+            "#},
+            DecoderOptions::default(),
+        )
+        .unwrap();
+
+        let config = Config {
+            name: "app",
+            extra: extra.clone(),
+            version: 2,
+        };
+
+        let expected = indoc! {r#"
+            name: "app"
+            extra:
+                db:
+                    hosts: ["a" "b"]
+            version: 2
+            "#};
+
+        let s = to_string(&config, EncoderOptions::default()).unwrap();
+        assert_eq!(s, expected);
+
+        let parsed = HuonValue::parse(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed["extra"], extra);
+    }
+
+    #[test]
+    fn test_pretty_aligned() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Inner {
+            id: i64,
+            description: String,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Config {
+            name: String,
+            age: i64,
+            last_name: String,
+            inner: Inner,
+        }
+
+        let config = Config {
+            name: "John".to_string(),
+            age: 32,
+            last_name: "Doe".to_string(),
+            inner: Inner {
+                id: 1,
+                description: "first".to_string(),
+            },
+        };
+
+        let s = to_string_pretty(&config, EncoderOptions::default()).unwrap();
+
+        let expected = indoc! {r#"
+            name:      "John"
+            age:       32
+            last_name: "Doe"
+            inner:
+                id:          1
+                description: "first"
+            "#};
+
+        assert_eq!(s, expected);
+
+        let parsed: Config = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn test_pretty_aligned_with_comments() {
+        let input = indoc! {r#"
+            # who
+            name: "John"
+            job:
+                # per month
+                pay: 4200
+                title: "Software Engineer"
+            age: 32"#};
+
+        let (map, comments) =
+            crate::parser::parse_with_comments(input, DecoderOptions::default()).unwrap();
+        let options = EncoderOptions {
+            align_values: true,
+            ..Default::default()
+        };
+        let s =
+            to_string_with_comments(&HuonValue::Object(map).sorted(), comments, options).unwrap();
+
+        let expected = indoc! {r#"
+            age:  32
+            job:
+                # per month
+                pay:   4200
+                title: "Software Engineer"
+            # who
+            name: "John"
+            "#};
+
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn test_streams_to_writer() {
+        /// Records every write instead of keeping the bytes.
+        #[derive(Default)]
+        struct CountingWriter {
+            calls: usize,
+            bytes: usize,
+            largest: usize,
+        }
+
+        impl io::Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.calls += 1;
+                self.bytes += buf.len();
+                self.largest = self.largest.max(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let map: BTreeMap<String, Vec<i64>> = (0..1000)
+            .map(|i| (format!("key_{i}"), vec![i, i + 1]))
+            .collect();
+
+        let mut writer = CountingWriter::default();
+        let mut serializer = HuonSerializer::new(&mut writer, EncoderOptions::default());
+        map.serialize(&mut serializer).unwrap();
+
+        // every entry arrives in several small pieces, none of them is held back
+        assert!(writer.calls > 1000 * 4);
+        assert!(writer.largest < 16);
+        assert_eq!(
+            writer.bytes,
+            to_string(&map, EncoderOptions::default()).unwrap().len() - 1
+        );
+
+        let options = EncoderOptions {
+            align_values: true,
+            ..Default::default()
+        };
+        let mut aligned = CountingWriter::default();
+        let mut serializer = HuonSerializer::new(&mut aligned, options);
+        map.serialize(&mut serializer).unwrap();
+
+        assert!(aligned.largest > writer.largest);
+    }
+
+    #[test]
+    fn test_max_line_width() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Lists {
+            short: Vec<i64>,
+            long: Vec<String>,
+            nested: Vec<Vec<i64>>,
+        }
+
+        let lists = Lists {
+            short: vec![1, 2, 3],
+            long: vec![
+                "alpha".to_string(),
+                "beta".to_string(),
+                "gamma".to_string(),
+                "delta".to_string(),
+            ],
+            nested: vec![vec![1, 2], (0..8).collect()],
+        };
+
+        let options = EncoderOptions {
+            max_line_width: Some(24),
+            ..Default::default()
+        };
+        let s = to_string(&lists, options).unwrap();
+
+        let expected = indoc! {r#"
+            short: [1 2 3]
+            long: [
+                "alpha"
+                "beta"
+                "gamma"
+                "delta"
+            ]
+            nested: [
+                [1 2]
+                [0 1 2 3 4 5 6 7]
+            ]
+            "#};
+
+        assert_eq!(s, expected);
+
+        let parsed: Lists = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, lists);
+
+        // everything fits
+        let options = EncoderOptions {
+            max_line_width: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&lists, options).unwrap(),
+            to_string(&lists, EncoderOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_128_bit_ints() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Ids {
+            signed: i128,
+            unsigned: u128,
+            small: i128,
+            list: Vec<i128>,
+        }
+
+        let ids = Ids {
+            signed: -170_141_183_460_469_231_731_687_303_715_884_105_728,
+            unsigned: 18_446_744_073_709_551_616_000,
+            small: 7,
+            list: vec![i128::from(i64::MAX) + 1, 1],
+        };
+
+        let s = to_string(&ids, EncoderOptions::default()).unwrap();
+
+        let expected = indoc! {"
+            signed: -170141183460469231731687303715884105728
+            unsigned: 18446744073709551616000
+            small: 7
+            list: [9223372036854775808 1]
+            "};
+
+        assert_eq!(s, expected);
+
+        let parsed: Ids = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, ids);
+
+        let value = HuonValue::parse(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(value["small"], HuonValue::Int(7));
+        assert_eq!(
+            value["unsigned"],
+            HuonValue::BigInt(18_446_744_073_709_551_616_000)
+        );
+
+        let by_id = std::collections::BTreeMap::from([(5_u128, 1), (u128::from(u64::MAX) + 1, 2)]);
+        let s = to_string(&by_id, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "`5`: 1\n`18446744073709551616`: 2\n");
+        let parsed: std::collections::BTreeMap<u128, i64> =
+            from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, by_id);
+
+        assert!(matches!(
+            to_string(&u128::MAX, EncoderOptions::default()),
+            Err(HuonSerializeError::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn test_result_like_enum() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        enum Outcome {
+            Ok(i64),
+            Err(String),
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Run {
+            first: Outcome,
+            second: Outcome,
+            all: Vec<Outcome>,
+            result: Result<i64, String>,
+        }
+
+        let run = Run {
+            first: Outcome::Ok(5),
+            second: Outcome::Err("timed out".to_string()),
+            all: vec![Outcome::Ok(1), Outcome::Err("no".to_string())],
+            result: Err("failed".to_string()),
+        };
+
+        let s = to_string(&run, EncoderOptions::default()).unwrap();
+
+        let expected = indoc! {r#"
+            first:
+                Ok: 5
+            second:
+                Err: "timed out"
+            all: [{ Ok: 1 } { Err: "no" }]
+            result:
+                Err: "failed"
+            "#};
+
+        assert_eq!(s, expected);
+
+        let parsed: Run = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, run);
+
+        let root: Outcome = from_str(
+            &to_string(&Outcome::Ok(2), EncoderOptions::default()).unwrap(),
+            DecoderOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(root, Outcome::Ok(2));
+    }
+
+    #[test]
+    fn test_inline_single_field() {
+        let person = Person {
+            name: "John",
+            job1: Job {
+                category: JobCategory {
+                    name: NewType("IT"),
+                },
+                info: JobInfo {
+                    pay: 4200.0,
+                    payrate: PayRate {
+                        iteration: "monthly",
+                        date: "Last Friday of every month",
+                        monthly_increase: Some("5%"),
+                    },
+                },
+                name: "Software Engineer",
+            },
+            age: 32,
+            job2: Job {
+                category: JobCategory {
+                    name: NewType("Security"),
+                },
+                info: JobInfo {
+                    pay: 3700.0,
+                    payrate: PayRate {
+                        iteration: "weekly",
+                        date: "Every Friday",
+                        monthly_increase: None,
+                    },
+                },
+                name: "Bodyguard",
+            },
+            last_name: "Doe",
+        };
+
+        let options = EncoderOptions {
+            inline_single_field: true,
+            ..Default::default()
+        };
+        let collapsed = to_string(&person, options).unwrap();
+        let expanded = to_string(&person, EncoderOptions::default()).unwrap();
+
+        assert!(collapsed.contains("    category: { name: \"IT\" }\n"));
+        assert!(expanded.contains("    category:\n        name: \"IT\"\n"));
+        assert_eq!(collapsed.lines().count() + 2, expanded.lines().count());
+
+        let parsed: Person = from_str(&collapsed, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, person);
+
+        // only scalars are pulled up
+        let nested: BTreeMap<&str, BTreeMap<&str, Vec<i64>>> =
+            BTreeMap::from([("outer", BTreeMap::from([("codes", vec![1, 2])]))]);
+        assert_eq!(
+            to_string(&nested, options).unwrap(),
+            "outer:\n    codes: [1 2]\n"
+        );
+    }
+
+    #[test]
+    fn test_optional_list() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Readings {
+            name: String,
+            values: Option<Vec<f64>>,
+        }
+
+        let some = Readings {
+            name: "temp".to_string(),
+            values: Some(vec![1.5, -2.25]),
+        };
+
+        let s = to_string(&some, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "name: \"temp\"\nvalues: [1.5 -2.25]\n");
+
+        let parsed: Readings = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, some);
+
+        let none = Readings {
+            name: "temp".to_string(),
+            values: None,
+        };
+
+        let s = to_string(&none, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "name: \"temp\"\nvalues: null\n");
+
+        let parsed: Readings = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, none);
+
+        let parsed: Readings = from_str("name: \"temp\"\n", DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, none);
+    }
+
+    #[test]
+    fn test_yes_no_bools() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Settings {
+            enabled: bool,
+            verbose: bool,
+            answer: String,
+            flags: Vec<bool>,
+        }
+
+        let settings = Settings {
+            enabled: true,
+            verbose: false,
+            answer: "no".to_string(),
+            flags: vec![false, true],
+        };
+
+        let encoder = EncoderOptions {
+            bool_style: BoolStyle::YesNo,
+            ..Default::default()
+        };
+        let decoder = DecoderOptions {
+            bool_style: BoolStyle::YesNo,
+            ..Default::default()
+        };
+
+        let s = to_string(&settings, encoder).unwrap();
+
+        let expected = indoc! {r#"
+            enabled: yes
+            verbose: no
+            answer: "no"
+            flags: [no yes]
+            "#};
+
+        assert_eq!(s, expected);
+
+        let parsed: Settings = from_str(&s, decoder).unwrap();
+        assert_eq!(parsed, settings);
+
+        // `true` and `false` still work, and keys named like the words are still keys
+        let value = HuonValue::parse("yes: true\nno: no", decoder).unwrap();
+        assert_eq!(value["yes"], HuonValue::Boolean(true));
+        assert_eq!(value["no"], HuonValue::Boolean(false));
+
+        assert!(from_str::<Settings>(&s, DecoderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_serialize_json_value() {
+        let json = serde_json::json!({
+            "name": "John",
+            "age": 32,
+            "pay": -4200.5,
+            "employed": true,
+            "manager": null,
+            "ids": [1, 18_446_744_073_709_551_615_u64],
+            "job": {
+                "title": "Software Engineer",
+                "level": 3
+            }
+        });
+
+        let s = to_string(&json, EncoderOptions::default()).unwrap();
+
+        // `serde_json::Value` keeps its keys sorted
+        let expected = indoc! {r#"
+            age: 32
+            employed: true
+            ids: [1 18446744073709551615]
+            job:
+                level: 3
+                title: "Software Engineer"
+            manager: null
+            name: "John"
+            pay: -4200.5
+            "#};
+
+        assert_eq!(s, expected);
+
+        let value = HuonValue::parse(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(value["job"]["level"], HuonValue::Int(3));
+        assert_eq!(
+            value["ids"],
+            HuonValue::List(vec![
+                HuonValue::Int(1),
+                HuonValue::BigInt(18_446_744_073_709_551_615)
+            ])
+        );
+
+        let parsed: serde_json::Value = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, json);
+    }
+
+    #[test]
+    fn test_u64_max_round_trip() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Counter {
+            hits: u64,
+            ids: Vec<u64>,
+        }
+
+        let counter = Counter {
+            hits: u64::MAX,
+            ids: vec![1, u64::MAX],
+        };
+
+        let s = to_string(&counter, EncoderOptions::default()).unwrap();
+        assert_eq!(
+            s,
+            "hits: 18446744073709551615\nids: [1 18446744073709551615]\n"
+        );
+
+        let parsed: Counter = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, counter);
+    }
+
+    #[test]
+    fn test_chars() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Csv {
+            separator: char,
+            quote: char,
+            escapes: Vec<char>,
+        }
+
+        let csv = Csv {
+            separator: ',',
+            quote: '\'',
+            escapes: vec![':', 'ß'],
+        };
+
+        let s = to_string(&csv, EncoderOptions::default()).unwrap();
+        assert_eq!(
+            s,
+            "separator: \",\"\nquote: \"'\"\nescapes: [\":\" \"ß\"]\n"
+        );
+
+        let parsed: Csv = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, csv);
+
+        let options = EncoderOptions {
+            bare_chars: true,
+            ..Default::default()
+        };
+        assert_eq!(to_string(&'a', options).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_value_round_trip_keeps_floats() {
+        let input = "codes: [111.0 222.0]\n";
+
+        let value =
+            HuonValue::Object(crate::parser::parse(input, DecoderOptions::default()).unwrap());
+        assert_eq!(
+            value,
+            HuonValue::Object(std::collections::HashMap::from([(
+                "codes",
+                HuonValue::List(vec![HuonValue::Float(111.0), HuonValue::Float(222.0)])
+            )]))
+        );
+
+        let s = to_string(&value, EncoderOptions::default()).unwrap();
+        assert_eq!(s, input);
+
+        let owned = to_string(&HuonValueOwned::from(&value), EncoderOptions::default()).unwrap();
+        assert_eq!(owned, input);
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let err = to_string(&vec![1.5, v], EncoderOptions::default()).unwrap_err();
+            assert!(matches!(err, HuonSerializeError::Custom(_)), "{err:?}");
+        }
+
+        let options = EncoderOptions {
+            float_precision: Some(2),
+            ..Default::default()
+        };
+        assert!(to_string(&HuonValue::Float(f64::NAN), options).is_err());
+    }
+
+    #[test]
+    fn test_keys_in_backticks() {
+        let map = BTreeMap::from([
+            ("my key", 1),
+            ("1abc", 2),
+            ("true", 3),
+            ("", 4),
+            ("plain_key", 5),
+        ]);
+
+        let s = to_string(&map, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "``: 4\n`1abc`: 2\n`my key`: 1\nplain_key: 5\ntrue: 3\n");
+
+        let parsed: BTreeMap<String, i64> = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(
+            parsed,
+            map.iter()
+                .map(|(key, value)| ((*key).to_string(), *value))
+                .collect()
+        );
+
+        for key in ["a`b", "a\nb"] {
+            let map = BTreeMap::from([(key, 1)]);
+            assert!(to_string(&map, EncoderOptions::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_integer_keys() {
+        let map = BTreeMap::from([
+            (-1, "minus one".to_string()),
+            (404, "not found".to_string()),
+        ]);
+
+        let s = to_string(&map, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "`-1`: \"minus one\"\n`404`: \"not found\"\n");
+
+        let parsed: BTreeMap<i64, String> = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, map);
+    }
+
+    #[test]
+    fn test_dedup_lists() {
+        #[derive(serde::Serialize)]
+        struct Set {
+            ids: Vec<i64>,
+            names: Vec<&'static str>,
+            mixed: Vec<HuonValue<'static>>,
+        }
+
+        let set = Set {
+            ids: vec![3, 1, 2, 1],
+            names: vec!["b", "a", "b"],
+            mixed: vec![
+                HuonValue::Int(2),
+                HuonValue::String("x".into()),
+                HuonValue::Int(2),
+            ],
+        };
+
+        let options = EncoderOptions {
+            dedup_lists: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&set, options).unwrap(),
+            "ids: [1 2 3]\nnames: [\"a\" \"b\"]\nmixed: [2 \"x\" 2]\n"
+        );
+
+        let options = EncoderOptions {
+            dedup_lists: true,
+            list_style: ListStyle::Newline,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&vec![3, 1, 2, 1], options).unwrap(),
+            "[\n    1\n    2\n    3\n]\n"
+        );
+
+        let options = EncoderOptions {
+            dedup_lists: true,
+            bare_chars: true,
+            float_precision: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string(&vec!['c', 'a', 'c', 'b'], options).unwrap(),
+            "[a b c]\n"
+        );
+        assert_eq!(
+            to_string(&vec![1.04, 0.5, 1.01], options).unwrap(),
+            "[0.5 1.0]\n"
+        );
+
+        // custom scalars are sorted by their text
+        let percents = vec![
+            scalar::Percent(0.05),
+            scalar::Percent(0.1),
+            scalar::Percent(0.05),
+        ];
+        assert_eq!(to_string(&percents, options).unwrap(), "[10% 5%]\n");
+    }
+
+    #[test]
+    fn test_tab_indentation() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Limits {
+            cpu: i64,
+            memory: String,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Service {
+            name: String,
+            limits: Limits,
+            ports: Vec<i64>,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Deployment {
+            service: Service,
+        }
+
+        let deployment = Deployment {
+            service: Service {
+                name: "api".to_string(),
+                limits: Limits {
+                    cpu: 2,
+                    memory: "512M".to_string(),
+                },
+                ports: vec![80, 443],
+            },
+        };
+
+        let options = EncoderOptions {
+            indent_char: IndentChar::Tab,
+            list_style: ListStyle::Newline,
+            ..Default::default()
+        };
+        let s = to_string(&deployment, options).unwrap();
+        assert_eq!(
+            s,
+            "service:\n\tname: \"api\"\n\tlimits:\n\t\tcpu: 2\n\t\tmemory: \"512M\"\n\tports: [\n\t\t80\n\t\t443\n\t]\n"
+        );
+
+        let options = DecoderOptions {
+            tab_width: Some(4),
+            ..Default::default()
+        };
+        let parsed: Deployment = from_str(&s, options).unwrap();
+        assert_eq!(parsed, deployment);
+    }
+
+    #[test]
+    fn test_skipped_fields() {
+        #[derive(serde::Serialize)]
+        struct Address {
+            city: &'static str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            zip: Option<&'static str>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct User {
+            name: &'static str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            nickname: Option<&'static str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            email: Option<&'static str>,
+            address: Address,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Profile {
+            user: User,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Account {
+            profile: Profile,
+        }
+
+        let user = User {
+            name: "John",
+            nickname: None,
+            email: Some("john@example.com"),
+            address: Address {
+                city: "Berlin",
+                zip: None,
+            },
+        };
+
+        let (s, skipped) = to_string_with_skipped(&user, EncoderOptions::default()).unwrap();
+        assert_eq!(s, to_string(&user, EncoderOptions::default()).unwrap());
+        assert!(!s.contains("nickname"));
+        assert_eq!(skipped, vec!["nickname", "zip"]);
+
+        let options = EncoderOptions {
+            align_values: true,
+            ..Default::default()
+        };
+        let (_, skipped) = to_string_with_skipped(&user, options).unwrap();
+        assert_eq!(skipped, vec!["nickname", "zip"]);
+
+        let options = EncoderOptions {
+            inline_single_field: true,
+            ..Default::default()
+        };
+        let account = Account {
+            profile: Profile { user },
+        };
+        let (s, skipped) = to_string_with_skipped(&account, options).unwrap();
+        assert_eq!(s, to_string(&account, options).unwrap());
+        assert_eq!(skipped, vec!["nickname", "zip"]);
+    }
+
+    #[test]
+    fn test_ip_addr() {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Listener {
+            address: IpAddr,
+            fallbacks: Vec<IpAddr>,
+        }
+
+        let listener = Listener {
+            address: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)),
+            fallbacks: vec![IpAddr::V6(Ipv6Addr::LOCALHOST)],
+        };
+
+        // written through `collect_str`, so like any other string
+        let s = to_string(&listener, EncoderOptions::default()).unwrap();
+        assert_eq!(s, "address: \"192.168.1.10\"\nfallbacks: [\"::1\"]\n");
+
+        let parsed: Listener = from_str(&s, DecoderOptions::default()).unwrap();
+        assert_eq!(parsed, listener);
     }
 }
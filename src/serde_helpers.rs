@@ -0,0 +1,121 @@
+//! Helpers for `#[serde(with = "...")]` on fields that serde can't handle nicely by itself.
+
+/// Reads and writes a [`Duration`](std::time::Duration) as a number with a unit, like `"30s"`
+/// or `"500ms"`. The units are `ns`, `us`, `ms`, `s`, `m` and `h`.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     #[serde(with = "huon::serde_helpers::duration")]
+///     timeout: Duration,
+/// }
+///
+/// let config: Config = huon::de::from_str("timeout: \"30s\"", Default::default()).unwrap();
+/// assert_eq!(config.timeout, Duration::from_secs(30));
+/// ```
+///
+/// With [`DecoderOptions::bareword_as_string`](crate::DecoderOptions::bareword_as_string)
+/// the quotes can be left out, `timeout: 30s`.
+pub mod duration {
+    use std::{fmt, time::Duration};
+
+    use serde::{Deserializer, Serializer, de};
+
+    /// Writes the duration in the largest unit that holds it exactly, e.g. `"1500ms"`.
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        let nanos = duration.subsec_nanos();
+
+        let s = if nanos == 0 {
+            format!("{}s", duration.as_secs())
+        } else if nanos.is_multiple_of(1_000_000) {
+            format!("{}ms", duration.as_millis())
+        } else if nanos.is_multiple_of(1_000) {
+            format!("{}us", duration.as_micros())
+        } else {
+            format!("{}ns", duration.as_nanos())
+        };
+
+        serializer.serialize_str(&s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        deserializer.deserialize_str(DurationVisitor)
+    }
+
+    struct DurationVisitor;
+
+    impl de::Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a duration like \"30s\" or \"500ms\"")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Duration, E> {
+            let invalid = || E::invalid_value(de::Unexpected::Str(v), &self);
+
+            let unit_start = v.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+            let (number, unit) = v.split_at(unit_start);
+            let number: u64 = number.parse().map_err(|_| invalid())?;
+
+            Ok(match unit {
+                "ns" => Duration::from_nanos(number),
+                "us" => Duration::from_micros(number),
+                "ms" => Duration::from_millis(number),
+                "s" => Duration::from_secs(number),
+                "m" => Duration::from_secs(number.checked_mul(60).ok_or_else(invalid)?),
+                "h" => Duration::from_secs(number.checked_mul(60 * 60).ok_or_else(invalid)?),
+                _ => return Err(invalid()),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{DecoderOptions, EncoderOptions, de::from_str, ser::to_string};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Timeouts {
+        #[serde(with = "super::duration")]
+        connect: Duration,
+        #[serde(with = "super::duration")]
+        read: Duration,
+    }
+
+    #[test]
+    fn test_duration() {
+        let expected = Timeouts {
+            connect: Duration::from_secs(30),
+            read: Duration::from_millis(500),
+        };
+
+        let input = "connect: \"30s\"\nread: \"500ms\"\n";
+        let timeouts: Timeouts = from_str(input, DecoderOptions::default()).unwrap();
+        assert_eq!(timeouts, expected);
+        assert_eq!(
+            to_string(&timeouts, EncoderOptions::default()).unwrap(),
+            input
+        );
+
+        let options = DecoderOptions {
+            bareword_as_string: true,
+            ..Default::default()
+        };
+        let timeouts: Timeouts = from_str("connect: 30s\nread: 500ms", options).unwrap();
+        assert_eq!(timeouts, expected);
+
+        let err = from_str::<Timeouts>("connect: \"30\"\nread: \"1s\"", DecoderOptions::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "connect: invalid value: string \"30\", expected a duration like \"30s\" or \"500ms\""
+        );
+    }
+}
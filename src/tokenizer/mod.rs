@@ -4,6 +4,8 @@ use std::str::CharIndices;
 
 use token::Token;
 
+use crate::{BoolStyle, DecoderOptions};
+
 pub mod token;
 
 #[derive(Debug, thiserror::Error, Clone, PartialEq)]
@@ -18,6 +20,17 @@ pub enum TokenizerError {
     #[error("Found an unexpected character: {_0}")]
     UnexpectedCharacter(char),
 
+    #[error("The number '{_0}' is malformed")]
+    MalformedNumber(String),
+
+    /// A [`DecoderOptions::digit_group_sep`] that's part of a number or the syntax around one.
+    #[error("'{_0}' can't be used to group digits")]
+    InvalidDigitGroupSep(char),
+
+    /// A string without its closing quote, `start` is the byte offset of the opening one.
+    #[error("The string starting at byte {start} is never closed")]
+    UnterminatedString { start: usize },
+
     #[error("Failed to parse a float: {_0}")]
     ParseFloatError(#[from] ParseFloatError),
 
@@ -31,28 +44,91 @@ type Result<T> = std::result::Result<T, TokenizerError>;
 pub struct Tokenizer<'a> {
     input: &'a str,
     char_indices: Peekable<CharIndices<'a>>,
+    options: DecoderOptions,
+    /// A problem with the options, which is the first and only thing the tokenizer yields.
+    invalid_options: Option<TokenizerError>,
 }
 
 impl<'a> Tokenizer<'a> {
     #[must_use]
     pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, DecoderOptions::default())
+    }
+
+    /// The options are checked right away. If they can't work, like a
+    /// [`DecoderOptions::digit_group_sep`] of `,`, the tokenizer yields nothing but that error.
+    #[must_use]
+    pub fn with_options(input: &'a str, options: DecoderOptions) -> Self {
+        // a `,` would glue `[1,2]` into `12`, so anything that can follow a number is rejected
+        let invalid_options = options
+            .digit_group_sep
+            .filter(|&sep| {
+                sep.is_ascii_alphanumeric()
+                    || sep.is_whitespace()
+                    || sep == options.quote_style.char()
+                    || ",.+-%#:[]{}`|".contains(sep)
+            })
+            .map(TokenizerError::InvalidDigitGroupSep);
+
         Self {
             input,
             char_indices: input.char_indices().peekable(),
+            options,
+            invalid_options,
         }
     }
+
+    /// The byte offset the next token starts at, or the input's length at the end.
+    pub fn offset(&mut self) -> usize {
+        self.char_indices
+            .peek()
+            .map_or(self.input.len(), |&(idx, _)| idx)
+    }
+
+    /// The whole input, including the parts that were already tokenized.
+    #[must_use]
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// Leaves out `WhiteSpace`, `Indent`, `NewLine` and `Comment` tokens, keeping only values and structural
+    /// tokens. Note that this loses the indentation, which the [`Parser`](crate::parser::Parser)
+    /// relies on.
+    pub fn significant(self) -> impl Iterator<Item = Result<Token<'a>>> {
+        self.filter(|token| !token.as_ref().is_ok_and(Token::is_trivia))
+    }
+
+    /// Collects the tokens [`Tokenizer::significant`] keeps, stopping at the first error.
+    /// Handy for asserting what an input means without spelling out its whitespace.
+    pub fn collect_significant(self) -> Result<Vec<Token<'a>>> {
+        self.significant().collect()
+    }
 }
 
 impl<'a> Iterator for Tokenizer<'a> {
     type Item = Result<Token<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.invalid_options.take() {
+            self.char_indices = self.input[self.input.len()..].char_indices().peekable();
+            return Some(Err(err));
+        }
+
         let (token_start_idx, char) = self.char_indices.next()?;
 
         let token_result = match char {
-            '"' => self.read_string(),
+            // a custom scalar like `#FF00FF`, see `scalar::HuonScalar`
+            _ if self.scalar_at(token_start_idx) => {
+                Ok(Token::Str(self.read_identifier(token_start_idx)))
+            }
 
-            char if char.is_ascii_digit() || char == '-' => self.read_number(token_start_idx),
+            char if char == self.options.quote_style.char() => {
+                self.read_string(char, token_start_idx)
+            }
+
+            char if char.is_ascii_digit() || char == '-' => self
+                .read_number(token_start_idx)
+                .map(|token| self.keep_number_text(token, token_start_idx)),
 
             char if is_valid_identifier_char(char) => {
                 let raw_ident = self.read_identifier(token_start_idx);
@@ -62,15 +138,31 @@ impl<'a> Iterator for Tokenizer<'a> {
                     return Some(Ok(Token::Identifier(raw_ident)));
                 }
 
-                parse_keyword(raw_ident).ok_or(TokenizerError::UnexpectedCharacter(char))
+                match parse_keyword(raw_ident, self.options.bool_style) {
+                    Some(token) => Ok(token),
+                    None if self.options.bareword_as_string => Ok(Token::Str(raw_ident)),
+                    // naming the whole word helps with keywords written without a space
+                    // in between, like `[truefalse]`
+                    None => Err(TokenizerError::InvalidIdentifier(raw_ident.to_string())),
+                }
             }
 
+            '`' => self.read_escaped_identifier(token_start_idx),
+
+            '|' => self.read_block(token_start_idx),
+
             '[' => Ok(Token::ListStart),
 
             ']' => Ok(Token::ListEnd),
 
+            '{' => Ok(Token::ObjectStart),
+
+            '}' => Ok(Token::ObjectEnd),
+
             ',' => Ok(Token::Separator),
 
+            '#' => Ok(self.read_comment(token_start_idx)),
+
             '\n' => Ok(Token::NewLine),
 
             '\r' => match self.char_indices.peek() {
@@ -82,11 +174,24 @@ impl<'a> Iterator for Tokenizer<'a> {
                 None => Ok(Token::NewLine),
             },
 
-            ' ' => self.read_whitespace(),
+            ' ' => self.read_whitespace(1),
+
+            '\t' => match self.options.tab_width {
+                Some(tab_width) => self.read_whitespace(tab_width as usize),
+                None => Err(TokenizerError::UnexpectedCharacter('\t')),
+            },
 
             c => Err(TokenizerError::UnexpectedCharacter(c)),
         };
 
+        let line_start = token_start_idx == 0 || self.input[..token_start_idx].ends_with('\n');
+        if let (Ok(Token::WhiteSpace(n)), true) = (&token_result, line_start) {
+            return match n.saturating_sub(self.options.base_indent) {
+                0 => self.next(),
+                n => Some(Ok(Token::Indent(n))),
+            };
+        }
+
         Some(token_result)
     }
 }
@@ -104,80 +209,295 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn read_string(&mut self) -> Result<Token<'a>> {
+    /// Reads a backtick delimited key like `` `true`: 1 ``. Unlike plain identifiers these may
+    /// contain any character but a backtick or a newline, and never turn into keywords.
+    fn read_escaped_identifier(&mut self, start_idx: usize) -> Result<Token<'a>> {
+        loop {
+            match self.char_indices.next() {
+                Some((end_idx, '`')) => {
+                    let ident = &self.input[start_idx + 1..end_idx];
+
+                    return match self.char_indices.next() {
+                        Some((_, ':')) => Ok(Token::Identifier(ident)),
+                        _ => Err(TokenizerError::InvalidIdentifier(
+                            self.input[start_idx..=end_idx].to_string(),
+                        )),
+                    };
+                }
+                Some((_, '\n')) => return Err(TokenizerError::UnexpectedCharacter('\n')),
+                Some(_) => {}
+                None => return Err(TokenizerError::EOF),
+            }
+        }
+    }
+
+    fn read_string(&mut self, quote: char, quote_idx: usize) -> Result<Token<'a>> {
+        let unterminated = TokenizerError::UnterminatedString { start: quote_idx };
+
         let start_idx = match self.char_indices.peek() {
             Some((idx, _)) => *idx,
-            None => return Err(TokenizerError::EOF),
+            None => return Err(unterminated),
         };
 
         loop {
             match self.char_indices.peek() {
-                Some((_, '"')) => {
+                Some((_, char)) if *char == quote => {
                     let (end_idx, _) = self.char_indices.next().unwrap(); // advance past the closing quote
                     return Ok(Token::Str(&self.input[start_idx..end_idx]));
                 }
                 Some(_) => {
                     self.char_indices.next();
                 }
-                None => return Err(TokenizerError::EOF),
+                None => return Err(unterminated),
             }
         }
     }
 
+    /// Reads a block scalar, which is every line after the `|` that's indented deeper than the
+    /// one it's on, ignoring blank lines. The line break after the last one is left alone, so
+    /// the block ends like any other value.
+    fn read_block(&mut self, pipe_idx: usize) -> Result<Token<'a>> {
+        let leading_spaces = |line: &str| line.len() - line.trim_start_matches(' ').len();
+
+        let line_start = self.input[..pipe_idx].rfind('\n').map_or(0, |idx| idx + 1);
+        let outer_indent = leading_spaces(&self.input[line_start..]);
+
+        // nothing but whitespace may follow the `|`
+        let header_end = self.input[pipe_idx..]
+            .find('\n')
+            .map_or(self.input.len(), |idx| pipe_idx + idx);
+        if let Some(c) = self.input[pipe_idx + 1..header_end]
+            .chars()
+            .find(|c| !c.is_whitespace())
+        {
+            return Err(TokenizerError::UnexpectedCharacter(c));
+        }
+
+        let mut start = None;
+        let mut end = header_end;
+        let mut indent = usize::MAX;
+        let mut line_start = header_end + 1;
+
+        while line_start < self.input.len() {
+            let line_end = self.input[line_start..]
+                .find('\n')
+                .map_or(self.input.len(), |idx| line_start + idx);
+            let line = self.input[line_start..line_end].trim_end_matches('\r');
+
+            if !line.trim().is_empty() {
+                let spaces = leading_spaces(line);
+                if spaces <= outer_indent {
+                    break;
+                }
+
+                start.get_or_insert(line_start);
+                indent = indent.min(spaces);
+                end = line_start + line.len();
+            }
+
+            line_start = line_end + 1;
+        }
+
+        while self.offset() < end {
+            self.char_indices.next();
+        }
+
+        Ok(match start {
+            Some(start) => Token::Block {
+                text: &self.input[start..end],
+                indent,
+            },
+            None => Token::Block {
+                text: "",
+                indent: 0,
+            },
+        })
+    }
+
+    /// Reads an int or a float. Besides plain decimals this supports scientific notation
+    /// (`1.5e3`), hex ints (`0xFF`) and underscores in between digits (`2_000`), or whatever
+    /// [`DecoderOptions::digit_group_sep`] is.
     fn read_number(&mut self, start_idx: usize) -> Result<Token<'a>> {
         let mut is_float = false;
+        let mut is_hex = false;
+        let group_sep = self.options.digit_group_sep;
+
+        let end_idx = loop {
+            let Some(&(idx, char)) = self.char_indices.peek() else {
+                break self.input.len();
+            };
+            let literal = &self.input[start_idx..idx];
+
+            match char {
+                char if Some(char) == group_sep => {}
+                char if char.is_ascii_digit() || (is_hex && char.is_ascii_hexdigit()) => {}
+                'x' if literal == "0" || literal == "-0" => is_hex = true,
+                '.' | 'e' | 'E' if !is_hex => is_float = true,
+                '+' | '-' if literal.ends_with(['e', 'E']) => {}
+                _ => break idx,
+            }
+            self.char_indices.next();
+        };
 
-        loop {
-            match self.char_indices.peek() {
-                Some((_, char)) if char.is_ascii_digit() => {
-                    self.char_indices.next();
-                }
-                Some((_, '.')) => {
-                    is_float = true;
-                    self.char_indices.next();
-                }
-                Some((end_idx, _)) => {
-                    let num_str = &self.input[start_idx..*end_idx];
-                    return if is_float {
-                        Ok(num_str.parse().map(Token::Float)?)
-                    } else {
-                        Ok(num_str.parse().map(Token::Int)?)
-                    };
-                }
-                None => {
-                    let num_str = &self.input[start_idx..];
-                    return if is_float {
-                        Ok(num_str.parse().map(Token::Float)?)
-                    } else {
-                        Ok(num_str.parse().map(Token::Int)?)
+        let percent = self.options.percent_literals
+            && self
+                .char_indices
+                .next_if(|&(_, char)| char == '%')
+                .is_some();
+
+        // a key like `1job` would otherwise be read as `1` and fail later on in the parser
+        if self
+            .char_indices
+            .peek()
+            .is_some_and(|&(_, char)| is_valid_identifier_char(char))
+        {
+            let ident = self.read_identifier(start_idx);
+
+            // e.g. `30s`, see `serde_helpers::duration`
+            if self.options.bareword_as_string {
+                return Ok(Token::Str(ident));
+            }
+            return Err(TokenizerError::InvalidIdentifier(ident.to_string()));
+        }
+
+        let literal = &self.input[start_idx..end_idx];
+        let malformed = || TokenizerError::MalformedNumber(literal.to_string());
+
+        // e.g. a stray `-`
+        if !literal.bytes().any(|b| b.is_ascii_digit()) {
+            return Err(malformed());
+        }
+
+        // every group separator has to sit in between two digits
+        let number = match group_sep {
+            Some(sep) => {
+                let bytes = literal.as_bytes();
+                for (idx, _) in literal.match_indices(sep) {
+                    let is_digit = |idx: Option<usize>| {
+                        idx.and_then(|idx| bytes.get(idx)).is_some_and(|b| {
+                            b.is_ascii_digit() || (is_hex && b.is_ascii_hexdigit())
+                        })
                     };
+                    if !is_digit(idx.checked_sub(1)) || !is_digit(Some(idx + sep.len_utf8())) {
+                        return Err(malformed());
+                    }
                 }
+                literal.replace(sep, "")
             }
+            None => literal.to_string(),
+        };
+
+        if !self.options.allow_leading_zeros {
+            let digits = number.trim_start_matches('-').as_bytes();
+            if let [b'0', b'0'..=b'9', ..] = digits {
+                return Err(malformed());
+            }
+        }
+
+        if percent {
+            if is_hex {
+                return Err(malformed());
+            }
+            return Ok(number.parse().map(Token::Percent)?);
+        }
+
+        if is_hex {
+            let (sign, digits) = match number.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", number.as_str()),
+            };
+            let digits = digits.strip_prefix("0x").ok_or_else(malformed)?;
+            if digits.is_empty() {
+                return Err(malformed());
+            }
+            return Ok(Token::Int(i64::from_str_radix(
+                &format!("{sign}{digits}"),
+                16,
+            )?));
+        }
+
+        if is_float {
+            Ok(number.parse().map(Token::Float)?)
+        } else if let Ok(int) = number.parse() {
+            Ok(Token::Int(int))
+        } else {
+            Ok(number.parse().map(Token::BigInt)?)
+        }
+    }
+
+    /// Pairs an int or float with its text, if [`DecoderOptions::keep_number_text`] is on.
+    fn keep_number_text(&mut self, token: Token<'a>, start_idx: usize) -> Token<'a> {
+        if !self.options.keep_number_text {
+            return token;
+        }
+
+        let raw = &self.input[start_idx..self.offset()];
+        match token {
+            Token::Int(value) => Token::RawInt { value, raw },
+            Token::Float(value) => Token::RawFloat { value, raw },
+            token => token,
         }
     }
 
-    fn read_whitespace(&mut self) -> Result<Token<'a>> {
-        let mut count = 1;
+    /// Reads a run of whitespace, measured in columns. A tab counts as `tab_width` columns.
+    /// Whitespace in front of a comment isn't meaningful, so it's folded into the comment.
+    fn read_whitespace(&mut self, mut count: usize) -> Result<Token<'a>> {
         loop {
-            match self.char_indices.peek() {
-                Some((_, ' ')) => {
-                    count += 1;
-                    self.char_indices.next();
+            match (self.char_indices.peek().copied(), self.options.tab_width) {
+                (Some((_, ' ')), _) => count += 1,
+                (Some((_, '\t')), Some(tab_width)) => count += tab_width as usize,
+                (Some((idx, '#')), _) if !self.scalar_at(idx) => {
+                    let (start_idx, _) = self.char_indices.next().unwrap();
+                    return Ok(self.read_comment(start_idx));
                 }
                 _ => return Ok(Token::WhiteSpace(count)),
             }
+            self.char_indices.next();
         }
     }
+
+    /// Whether a custom scalar starts at `idx`, see [`DecoderOptions::scalar_prefix`].
+    fn scalar_at(&self, idx: usize) -> bool {
+        let mut chars = self.input[idx..].chars();
+
+        chars.next() == self.options.scalar_prefix
+            && chars.next().is_some_and(is_valid_identifier_char)
+    }
+
+    /// Reads a comment starting at the `#` at `start_idx`. The line break is left for the caller.
+    fn read_comment(&mut self, start_idx: usize) -> Token<'a> {
+        let end_idx = loop {
+            match self.char_indices.peek() {
+                Some((end_idx, '\n' | '\r')) => break *end_idx,
+                Some(_) => {
+                    self.char_indices.next();
+                }
+                None => break self.input.len(),
+            }
+        };
+
+        Token::Comment(self.input[start_idx + 1..end_idx].trim())
+    }
 }
 
 fn is_valid_identifier_char(char: char) -> bool {
     (char.is_ascii_alphabetic() || char.is_ascii_digit()) || ['_'].contains(&char)
 }
 
-fn parse_keyword(input: &str) -> Option<Token<'_>> {
+/// Whether `key` can be written without backticks, so it isn't read as a number.
+pub(crate) fn is_plain_key(key: &str) -> bool {
+    key.chars()
+        .next()
+        .is_some_and(|char| !char.is_ascii_digit())
+        && key.chars().all(is_valid_identifier_char)
+}
+
+fn parse_keyword(input: &str, bool_style: BoolStyle) -> Option<Token<'_>> {
     Some(match input {
         "true" => Token::Boolean(true),
         "false" => Token::Boolean(false),
+        "yes" if bool_style == BoolStyle::YesNo => Token::Boolean(true),
+        "no" if bool_style == BoolStyle::YesNo => Token::Boolean(false),
         "null" => Token::Null,
         _ => return None,
     })
@@ -185,10 +505,12 @@ fn parse_keyword(input: &str) -> Option<Token<'_>> {
 
 #[cfg(test)]
 mod test {
+    use indoc::indoc;
     use pretty_assertions::assert_eq;
 
     use crate::tokenizer::TokenizerError;
     use crate::tokenizer::token::Token;
+    use crate::{DecoderOptions, QuoteStyle};
 
     use super::Result;
     use super::Tokenizer;
@@ -203,6 +525,20 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn read_string_single_quoted() -> std::result::Result<(), TokenizerError> {
+        let input = r#"'say "hi"'"#;
+        let options = DecoderOptions {
+            quote_style: QuoteStyle::Single,
+            ..Default::default()
+        };
+        let tokens: Vec<_> = Tokenizer::with_options(input, options).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![Token::Str(r#"say "hi""#)]);
+
+        Ok(())
+    }
+
     #[test]
     fn identifier() -> std::result::Result<(), TokenizerError> {
         let input = "job1: \"swe\"";
@@ -220,6 +556,36 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn escaped_identifier() -> std::result::Result<(), TokenizerError> {
+        let input = "`true`: 1 `my key`: 2";
+        let tokens: Vec<_> = Tokenizer::new(input).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("true"),
+                Token::WhiteSpace(1),
+                Token::Int(1),
+                Token::WhiteSpace(1),
+                Token::Identifier("my key"),
+                Token::WhiteSpace(1),
+                Token::Int(2),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_identifier_without_colon() {
+        let err = Tokenizer::new("`true` 1")
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+
+        assert_eq!(err, TokenizerError::InvalidIdentifier("`true`".to_string()));
+    }
+
     #[test]
     fn read_number_i64() -> std::result::Result<(), TokenizerError> {
         let input = "number: 69420";
@@ -304,13 +670,13 @@ mod test {
                 Token::WhiteSpace(1),
                 Token::ListStart,
                 Token::NewLine,
-                Token::WhiteSpace(4),
+                Token::Indent(4),
                 Token::Float(-3.5),
                 Token::NewLine,
-                Token::WhiteSpace(4),
+                Token::Indent(4),
                 Token::Float(2.5),
                 Token::NewLine,
-                Token::WhiteSpace(4),
+                Token::Indent(4),
                 Token::Float(1.1),
                 Token::NewLine,
                 Token::ListEnd,
@@ -367,4 +733,416 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn read_tab_indentation() -> std::result::Result<(), TokenizerError> {
+        let input = "info:\n\t\tpay: 1";
+        let options = DecoderOptions {
+            tab_width: Some(4),
+            ..Default::default()
+        };
+        let tokens: Vec<_> = Tokenizer::with_options(input, options).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("info"),
+                Token::NewLine,
+                Token::Indent(8),
+                Token::Identifier("pay"),
+                Token::WhiteSpace(1),
+                Token::Int(1),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_tab_by_default() {
+        let err = Tokenizer::new("info:\n\tpay: 1")
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+
+        assert_eq!(err, TokenizerError::UnexpectedCharacter('\t'));
+    }
+
+    #[test]
+    fn significant_tokens() -> std::result::Result<(), TokenizerError> {
+        let input = "job1:\n    info:\n        codes: [1, 2]\n    name: \"swe\"";
+        let tokens = Tokenizer::new(input).collect_significant()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("job1"),
+                Token::Identifier("info"),
+                Token::Identifier("codes"),
+                Token::ListStart,
+                Token::Int(1),
+                Token::Separator,
+                Token::Int(2),
+                Token::ListEnd,
+                Token::Identifier("name"),
+                Token::Str("swe"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_significant_nested() -> std::result::Result<(), TokenizerError> {
+        let input = indoc! {r#"
+            # people
+            person:
+                name: "John"  # first name only
+                jobs: [
+                    { title: "swe", pay: 4200.5 }
+                    { title: "cook", pay: null }
+                ]
+        "#};
+
+        assert_eq!(
+            Tokenizer::new(input).collect_significant()?,
+            vec![
+                Token::Identifier("person"),
+                Token::Identifier("name"),
+                Token::Str("John"),
+                Token::Identifier("jobs"),
+                Token::ListStart,
+                Token::ObjectStart,
+                Token::Identifier("title"),
+                Token::Str("swe"),
+                Token::Separator,
+                Token::Identifier("pay"),
+                Token::Float(4200.5),
+                Token::ObjectEnd,
+                Token::ObjectStart,
+                Token::Identifier("title"),
+                Token::Str("cook"),
+                Token::Separator,
+                Token::Identifier("pay"),
+                Token::Null,
+                Token::ObjectEnd,
+                Token::ListEnd,
+            ]
+        );
+
+        // the indentation is only visible in the full stream
+        let input = "a:\n    b: 1";
+        assert_eq!(
+            Tokenizer::new(input).collect_significant()?,
+            vec![
+                Token::Identifier("a"),
+                Token::Identifier("b"),
+                Token::Int(1)
+            ]
+        );
+        assert!(
+            Tokenizer::new(input)
+                .collect::<Result<Vec<_>>>()?
+                .contains(&Token::Indent(4))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_significant_error() {
+        assert_eq!(
+            Tokenizer::new("a: [1 \"b]").collect_significant(),
+            Err(TokenizerError::UnterminatedString { start: 6 })
+        );
+    }
+
+    #[test]
+    fn read_comment() -> std::result::Result<(), TokenizerError> {
+        let input = "# note\nage: 32 # years\n    #indented";
+        let tokens: Vec<_> = Tokenizer::new(input).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Comment("note"),
+                Token::NewLine,
+                Token::Identifier("age"),
+                Token::WhiteSpace(1),
+                Token::Int(32),
+                Token::Comment("years"),
+                Token::NewLine,
+                Token::Comment("indented"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn read_number_extended() -> std::result::Result<(), TokenizerError> {
+        let input = "[1e3 -1.5E-2 2_000 0xFF -0x1_0 6.25e+1]";
+        let tokens = Tokenizer::new(input).collect_significant()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::ListStart,
+                Token::Float(1000.0),
+                Token::Float(-0.015),
+                Token::Int(2000),
+                Token::Int(255),
+                Token::Int(-16),
+                Token::Float(62.5),
+                Token::ListEnd,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_number_i128() -> std::result::Result<(), TokenizerError> {
+        let input = "[9223372036854775807 9223372036854775808 -9223372036854775809]";
+        let tokens = Tokenizer::new(input).collect_significant()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::ListStart,
+                Token::Int(i64::MAX),
+                Token::BigInt(i128::from(i64::MAX) + 1),
+                Token::BigInt(i128::from(i64::MIN) - 1),
+                Token::ListEnd,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_number_malformed() {
+        for input in ["1__000", "1_", "1_.5", "1e_3", "0x", "0x_1", "-", "-e"] {
+            let err = Tokenizer::new(input)
+                .collect::<Result<Vec<_>>>()
+                .unwrap_err();
+
+            assert_eq!(err, TokenizerError::MalformedNumber(input.to_string()));
+        }
+    }
+
+    #[test]
+    fn leading_zeros() -> std::result::Result<(), TokenizerError> {
+        let tokens: Vec<_> = Tokenizer::new("007").collect::<Result<Vec<_>>>()?;
+        assert_eq!(tokens, vec![Token::Int(7)]);
+
+        let options = DecoderOptions {
+            allow_leading_zeros: false,
+            ..Default::default()
+        };
+        for input in ["007", "-01", "00.5", "0_1"] {
+            let err = Tokenizer::with_options(input, options)
+                .collect::<Result<Vec<_>>>()
+                .unwrap_err();
+            assert_eq!(err, TokenizerError::MalformedNumber(input.to_string()));
+        }
+
+        let tokens: Vec<_> =
+            Tokenizer::with_options("[0 -0.5 0x0F 10]", options).collect_significant()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::ListStart,
+                Token::Int(0),
+                Token::Float(-0.5),
+                Token::Int(15),
+                Token::Int(10),
+                Token::ListEnd,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn identifier_starting_with_digit() {
+        let err = Tokenizer::new("1job: 1")
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+
+        assert_eq!(err, TokenizerError::InvalidIdentifier("1job".to_string()));
+    }
+
+    #[test]
+    fn unterminated_string() {
+        for (input, start) in [("name: \"John\nage: 32", 6), ("a: \"b\"\nc: \"", 10)] {
+            let err = Tokenizer::new(input)
+                .collect::<Result<Vec<_>>>()
+                .unwrap_err();
+
+            assert_eq!(err, TokenizerError::UnterminatedString { start });
+            assert_eq!(&input[start..=start], "\"");
+        }
+    }
+
+    #[test]
+    fn indent_only_at_line_start() -> std::result::Result<(), TokenizerError> {
+        let input = "  a:  1\n    b: [1  2]\nc: 3";
+        let tokens: Vec<_> = Tokenizer::new(input).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Indent(2),
+                Token::Identifier("a"),
+                Token::WhiteSpace(2),
+                Token::Int(1),
+                Token::NewLine,
+                Token::Indent(4),
+                Token::Identifier("b"),
+                Token::WhiteSpace(1),
+                Token::ListStart,
+                Token::Int(1),
+                Token::WhiteSpace(2),
+                Token::Int(2),
+                Token::ListEnd,
+                Token::NewLine,
+                Token::Identifier("c"),
+                Token::WhiteSpace(1),
+                Token::Int(3),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn percent() -> std::result::Result<(), TokenizerError> {
+        let options = DecoderOptions {
+            percent_literals: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Tokenizer::with_options("increase: 5%\nrate: [-1.5% 7]", options)
+                .collect_significant()?,
+            vec![
+                Token::Identifier("increase"),
+                Token::Percent(5.0),
+                Token::Identifier("rate"),
+                Token::ListStart,
+                Token::Percent(-1.5),
+                Token::Int(7),
+                Token::ListEnd,
+            ]
+        );
+
+        assert_eq!(
+            Tokenizer::new("increase: 5%").collect_significant(),
+            Err(TokenizerError::UnexpectedCharacter('%'))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn keep_number_text() -> std::result::Result<(), TokenizerError> {
+        let input = "a: 1_000\nb: [0xFF 1.50 -2e3]\nc: 7";
+        let options = DecoderOptions {
+            keep_number_text: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Tokenizer::with_options(input, options).collect_significant()?,
+            vec![
+                Token::Identifier("a"),
+                Token::RawInt {
+                    value: 1000,
+                    raw: "1_000"
+                },
+                Token::Identifier("b"),
+                Token::ListStart,
+                Token::RawInt {
+                    value: 255,
+                    raw: "0xFF"
+                },
+                Token::RawFloat {
+                    value: 1.5,
+                    raw: "1.50"
+                },
+                Token::RawFloat {
+                    value: -2000.0,
+                    raw: "-2e3"
+                },
+                Token::ListEnd,
+                Token::Identifier("c"),
+                Token::RawInt { value: 7, raw: "7" },
+            ]
+        );
+
+        assert_eq!(
+            crate::parser::parse(input, options),
+            crate::parser::parse(input, DecoderOptions::default())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn digit_group_sep() -> std::result::Result<(), TokenizerError> {
+        let options = DecoderOptions {
+            digit_group_sep: Some('\''),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Tokenizer::with_options("[1'000'000 -2'500.5 0xF'F]", options).collect_significant()?,
+            vec![
+                Token::ListStart,
+                Token::Int(1_000_000),
+                Token::Float(-2500.5),
+                Token::Int(255),
+                Token::ListEnd,
+            ]
+        );
+
+        for input in ["1''000", "1'", "1_000"] {
+            assert!(
+                Tokenizer::with_options(input, options)
+                    .collect_significant()
+                    .is_err()
+            );
+        }
+
+        let options = DecoderOptions {
+            digit_group_sep: None,
+            ..Default::default()
+        };
+        assert_eq!(
+            Tokenizer::with_options("1_000", options).collect_significant(),
+            Err(TokenizerError::InvalidIdentifier("1_000".to_string()))
+        );
+
+        for sep in [',', '.', 'e', '-', ' ', '"'] {
+            let options = DecoderOptions {
+                digit_group_sep: Some(sep),
+                ..Default::default()
+            };
+            assert_eq!(
+                Tokenizer::with_options("[1,2]", options).collect_significant(),
+                Err(TokenizerError::InvalidDigitGroupSep(sep))
+            );
+
+            // even without a number in sight
+            let mut tokenizer = Tokenizer::with_options("a: \"b\"", options);
+            assert_eq!(
+                tokenizer.next(),
+                Some(Err(TokenizerError::InvalidDigitGroupSep(sep)))
+            );
+            assert_eq!(tokenizer.next(), None);
+        }
+
+        Ok(())
+    }
 }
@@ -3,14 +3,41 @@ pub enum Token<'a> {
     Identifier(&'a str),
     Str(&'a str),
     Int(i64),
+    /// An integer too large for an `i64`.
+    BigInt(i128),
     Float(f64),
+    /// A number followed by `%`, as it's written, so `5` for `5%`.
+    Percent(f64),
+    /// An `Int` along with its text, see [`DecoderOptions::keep_number_text`](crate::DecoderOptions::keep_number_text).
+    RawInt {
+        value: i64,
+        raw: &'a str,
+    },
+    /// A `Float` along with its text, see [`DecoderOptions::keep_number_text`](crate::DecoderOptions::keep_number_text).
+    RawFloat {
+        value: f64,
+        raw: &'a str,
+    },
+    /// A block scalar, the lines indented below a `|`. `text` is them as they're written,
+    /// `indent` how many spaces they have in common.
+    Block {
+        text: &'a str,
+        indent: usize,
+    },
     NewLine,
     WhiteSpace(usize),
+    /// Whitespace at the start of a line, how many columns it indents the line by.
+    Indent(usize),
     Boolean(bool),
     Null,
     ListStart,
     ListEnd,
+    ObjectStart,
+    ObjectEnd,
     Separator,
+
+    /// The text of a `#` comment, up to the end of the line.
+    Comment(&'a str),
 }
 
 impl Token<'_> {
@@ -21,9 +48,91 @@ impl Token<'_> {
             Token::Identifier(_)
                 | Token::Str(_)
                 | Token::Int(_)
+                | Token::BigInt(_)
                 | Token::Float(_)
+                | Token::Percent(_)
+                | Token::RawInt { .. }
+                | Token::RawFloat { .. }
+                | Token::Block { .. }
                 | Token::Boolean(_)
                 | Token::Null
         )
     }
+
+    /// Whitespace, line breaks and comments, which don't change what a document means
+    /// (apart from the indentation they carry).
+    #[must_use]
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            Token::WhiteSpace(_) | Token::Indent(_) | Token::NewLine | Token::Comment(_)
+        )
+    }
+
+    /// Brackets, braces and commas.
+    #[must_use]
+    pub fn is_structural(&self) -> bool {
+        matches!(
+            self,
+            Token::ListStart
+                | Token::ListEnd
+                | Token::ObjectStart
+                | Token::ObjectEnd
+                | Token::Separator
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn classification() {
+        let tokens = [
+            Token::Identifier("key"),
+            Token::Str("s"),
+            Token::Int(1),
+            Token::BigInt(1),
+            Token::Float(1.5),
+            Token::NewLine,
+            Token::WhiteSpace(4),
+            Token::Boolean(true),
+            Token::Null,
+            Token::ListStart,
+            Token::ListEnd,
+            Token::ObjectStart,
+            Token::ObjectEnd,
+            Token::Separator,
+            Token::Comment("c"),
+        ];
+
+        let classes: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.is_value(), token.is_trivia(), token.is_structural()))
+            .collect();
+
+        assert_eq!(
+            classes,
+            vec![
+                (true, false, false),
+                (true, false, false),
+                (true, false, false),
+                (true, false, false),
+                (true, false, false),
+                (false, true, false),
+                (false, true, false),
+                (true, false, false),
+                (true, false, false),
+                (false, false, true),
+                (false, false, true),
+                (false, false, true),
+                (false, false, true),
+                (false, false, true),
+                (false, true, false),
+            ]
+        );
+    }
 }
@@ -0,0 +1,55 @@
+use std::{env, fs, process::Command};
+
+use huon::{DecoderOptions, parser::value::HuonValue};
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_huon"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_to_json() {
+    let json = run(&["to-json", "test.huon"]);
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(json["name"], "John");
+    assert_eq!(json["age"], 32);
+    assert_eq!(json["job1"]["info"]["pay"], -4200.5);
+    assert_eq!(json["job2"]["info"]["payrate"]["date"], "Every Friday");
+}
+
+#[test]
+fn test_round_trip() {
+    let json = run(&["to-json", "test.huon"]);
+
+    let path = env::temp_dir().join(format!("huon-cli-{}.json", std::process::id()));
+    fs::write(&path, json).unwrap();
+    let huon = run(&["from-json", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    let original = fs::read_to_string("test.huon").unwrap();
+    assert_eq!(
+        HuonValue::parse(&huon, DecoderOptions::default()).unwrap(),
+        HuonValue::parse(&original, DecoderOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn test_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_huon"))
+        .arg("to-json")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Usage:"));
+}